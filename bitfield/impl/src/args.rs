@@ -0,0 +1,179 @@
+//! `#[bitfield(...)]`中的参数
+
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+use crate::config;
+
+/// `#[bitfield(...)]`的参数，未写出的参数取默认值
+#[derive(Default)]
+pub(crate) struct BitfieldArgs {
+    /// 字段从存储的哪一端开始排列
+    pub bit_order: BitOrder,
+    /// 存储的字节序
+    pub endian: Endian,
+    /// setter收到超出字段位数的值时的处理方式
+    pub overflow: Overflow,
+    /// `#[bitfield(atomic)]`，以原子整数存储
+    pub atomic: bool,
+    /// `#[bitfield(bytes = N)]`中指定的存储字节数
+    pub bytes: Option<syn::LitInt>,
+    /// `#[bitfield(repr = u32)]`中指定的存储整数类型
+    pub repr: Option<syn::Ident>,
+}
+
+/// 位序，`bit_order = "lsb0"`或`bit_order = "msb0"`
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BitOrder {
+    /// 第一个字段位于最低位
+    #[default]
+    Lsb0,
+    /// 第一个字段位于最高位
+    Msb0,
+}
+
+/// 字节序，`endian = "little"`或`endian = "big"`
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endian {
+    /// 第一个字节存放最低的8位
+    #[default]
+    Little,
+    /// 第一个字节存放最高的8位
+    Big,
+}
+
+/// 溢出处理方式，`overflow = "panic"`、`overflow = "truncate"`或`overflow = "debug_assert"`
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Overflow {
+    /// 直接panic
+    #[default]
+    Panic,
+    /// 丢弃超出的高位
+    Truncate,
+    /// 仅在debug构建中panic，release构建中丢弃超出的高位
+    DebugAssert,
+}
+
+impl Parse for BitfieldArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        for meta in Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated(input)? {
+            let path = meta.path();
+            if path.is_ident(config::BIT_ORDER_ARG) {
+                args.bit_order =
+                    choice(&meta, &[("lsb0", BitOrder::Lsb0), ("msb0", BitOrder::Msb0)])?;
+            } else if path.is_ident(config::ENDIAN_ARG) {
+                args.endian = choice(&meta, &[("little", Endian::Little), ("big", Endian::Big)])?;
+            } else if path.is_ident(config::OVERFLOW_ARG) {
+                args.overflow = choice(
+                    &meta,
+                    &[
+                        ("panic", Overflow::Panic),
+                        ("truncate", Overflow::Truncate),
+                        ("debug_assert", Overflow::DebugAssert),
+                    ],
+                )?;
+            } else if path.is_ident(config::BYTES_ARG) {
+                args.bytes = Some(bytes(&meta)?);
+            } else if path.is_ident(config::REPR_ARG) {
+                args.repr = Some(repr(&meta)?);
+            } else if path.is_ident(config::ATOMIC_ARG) {
+                meta.require_path_only()?;
+                args.atomic = true;
+            } else {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "unknown #[bitfield] argument",
+                ));
+            }
+        }
+        let storage = [
+            (args.atomic, config::ATOMIC_ARG),
+            (args.bytes.is_some(), config::BYTES_ARG),
+            (args.repr.is_some(), config::REPR_ARG),
+        ];
+        let mut given = storage
+            .iter()
+            .filter(|(given, _)| *given)
+            .map(|(_, name)| *name);
+        if let (Some(first), Some(second)) = (given.next(), given.next()) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "#[bitfield] arguments `{}` and `{}` cannot be combined",
+                    first, second
+                ),
+            ));
+        }
+        Ok(args)
+    }
+}
+
+/// 解析`bytes = N`，`N`必须是正整数
+fn bytes(meta: &syn::Meta) -> syn::Result<syn::LitInt> {
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(ref lit),
+                ..
+            }),
+        ..
+    }) = meta
+    {
+        if lit.base10_parse::<usize>()? > 0 {
+            return Ok(lit.clone());
+        }
+    }
+    Err(syn::Error::new_spanned(
+        meta,
+        "expected `bytes = N` with N > 0",
+    ))
+}
+
+/// 解析`repr = u32`，只接受`u8`、`u16`、`u32`与`u64`
+fn repr(meta: &syn::Meta) -> syn::Result<syn::Ident> {
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Path(ref path),
+        ..
+    }) = meta
+    {
+        if let Some(ident) = path.path.get_ident() {
+            if ["u8", "u16", "u32", "u64"].iter().any(|ty| ident == ty) {
+                return Ok(ident.clone());
+            }
+        }
+    }
+    Err(syn::Error::new_spanned(
+        meta,
+        "expected `repr = u8`, `repr = u16`, `repr = u32` or `repr = u64`",
+    ))
+}
+
+/// 将`key = "value"`中的字符串映射为`choices`中对应的值
+fn choice<T: Copy>(meta: &syn::Meta, choices: &[(&str, T)]) -> syn::Result<T> {
+    let expected = choices
+        .iter()
+        .map(|(name, _)| format!("`\"{}\"`", name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(ref lit),
+                ..
+            }),
+        ..
+    }) = meta
+    {
+        let value = lit.value();
+        return choices
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, choice)| *choice)
+            .ok_or_else(|| syn::Error::new_spanned(lit, format!("expected one of {}", expected)));
+    }
+    Err(syn::Error::new_spanned(
+        meta,
+        format!("expected a string value, one of {}", expected),
+    ))
+}
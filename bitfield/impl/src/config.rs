@@ -0,0 +1,65 @@
+//! 用于配置宏的属性
+
+/// 字段上用于声明位数的标签，例如`#[bits = 3]`
+pub const BITS_ATTR: &str = "bits";
+
+/// `#[bitfield(bit_order = "msb0")]`，选择字段从最低位还是最高位开始排列
+pub const BIT_ORDER_ARG: &str = "bit_order";
+
+/// `#[bitfield(endian = "big")]`，选择存储的字节序
+pub const ENDIAN_ARG: &str = "endian";
+
+/// `#[bitfield(overflow = "truncate")]`，选择setter收到超出字段位数的值时的处理方式
+pub const OVERFLOW_ARG: &str = "overflow";
+
+/// `#[bitfield(bytes = 4)]`，指定存储的字节数，字段之后的位补零
+pub const BYTES_ARG: &str = "bytes";
+
+/// `#[bitfield(repr = u32)]`，以无符号整数而不是字节数组存储
+pub const REPR_ARG: &str = "repr";
+
+/// `#[bitfield(atomic)]`，以原子整数存储，访问方法通过`&self`原子地读写字段
+pub const ATOMIC_ARG: &str = "atomic";
+
+/// 写在`#[bitfield]`结构体上时由`#[bitfield]`接管的derive，使结构体可以嵌套在其他位域中
+pub const SPECIFIER_DERIVE: &str = "BitfieldSpecifier";
+
+/// 写在`#[bitfield]`结构体上时由`#[bitfield]`接管的derive，逐个字段输出解码后的值
+pub const DEBUG_DERIVE: &str = "Debug";
+
+/// 同[`DEBUG_DERIVE`]，字段可以用`#[debug = "..."]`指定输出格式
+pub const CUSTOM_DEBUG_DERIVE: &str = "CustomDebug";
+
+/// 写在`#[bitfield]`结构体上时由`#[bitfield]`接管的derive，逐个字段比较
+pub const PARTIAL_EQ_DERIVE: &str = "PartialEq";
+
+/// 写在`#[bitfield]`结构体上时由`#[bitfield]`接管的derive，逐个字段计算哈希
+pub const HASH_DERIVE: &str = "Hash";
+
+/// `#[derive(BitfieldSpecifier)]`枚举上的`#[specifier(...)]`标签
+pub const SPECIFIER_ATTR: &str = "specifier";
+
+/// `#[specifier(serde)]`，为枚举生成以变体名表示的serde实现，需要开启`serde` feature
+pub const SERDE_ARG: &str = "serde";
+
+/// 写在`#[bitfield]`结构体上时由`#[bitfield]`接管的derive，需要开启`serde` feature，
+/// 人类可读格式中序列化为字段名到值的映射，其他格式中序列化为存储的字节
+pub const SERIALIZE_DERIVE: &str = "Serialize";
+
+/// 同[`SERIALIZE_DERIVE`]，用于反序列化
+pub const DESERIALIZE_DERIVE: &str = "Deserialize";
+
+/// 字段上用于指定`Debug`输出格式的标签，例如`#[debug = "0b{:08b}"]`
+pub const DEBUG_ATTR: &str = "debug";
+
+/// 字段上用于跳过访问方法的标签，`#[skip]`跳过全部，`#[skip(getters)]`或`#[skip(setters)]`只跳过一种
+pub const SKIP_ATTR: &str = "skip";
+
+/// `#[skip(getters)]`，字段只写
+pub const SKIP_GETTERS: &str = "getters";
+
+/// `#[skip(setters)]`，字段只读
+pub const SKIP_SETTERS: &str = "setters";
+
+/// 字段上的`#[const_with]`，声明字段类型由`#[derive(BitfieldSpecifier)]`生成，使其`with_`成为`const fn`
+pub const CONST_WITH_ATTR: &str = "const_with";
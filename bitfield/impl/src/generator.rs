@@ -0,0 +1,1078 @@
+//! 位域生成器，用于生成`#[bitfield]`的各项内容
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use crate::args::Overflow;
+use crate::model::{BitfieldConfig, FieldConfig};
+use crate::utils;
+
+/// 位域生成器
+pub(crate) struct Generator;
+
+impl Generator {
+    /// 为写了`#[derive(BitfieldSpecifier)]`的位域实现`bitfield::Specifier`，使其可以作为其他位域的字段
+    /// 整个存储被看作一个整数，总位数不能超过64
+    /// ```ignore
+    /// impl ::bitfield::Specifier for Flags {
+    ///     const BITS: usize = 8;
+    ///     type Bytes = u8;
+    ///     type InOut = Flags;
+    ///     type Output = Flags;
+    ///     fn into_bytes(input: Flags) -> u8 { ... }
+    ///     fn from_bytes(bytes: u8) -> Flags { ... }
+    /// }
+    /// impl Flags {
+    ///     pub const fn __bitfield_into_raw(input: Flags) -> u64 { ... }
+    /// }
+    /// ```
+    /// `__bitfield_into_raw`供外层位域中标有`#[const_with]`的字段在`const fn`中转换
+    pub fn generate_specifier(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        if !config.derives.specifier {
+            return Ok(TokenStream2::new());
+        }
+        let vis = config.vis;
+        let name = config.name;
+        let total_bits = config.total_bits();
+        let read = config.read_storage(quote!(input), quote!(0), total_bits.clone());
+        let write = config.write_storage(
+            quote!(value),
+            quote!(0),
+            total_bits.clone(),
+            quote!(bytes.into()),
+        );
+        let message = format!(
+            "#[bitfield] struct `{}` is wider than the 64 bits a Specifier can hold",
+            name
+        );
+        Ok(quote! {
+            const _: () = assert!(#total_bits <= 64, #message);
+
+            impl ::bitfield::Specifier for #name {
+                const BITS: usize = #total_bits;
+                type Bytes = <::bitfield::__private::BitCount<{ #total_bits }>
+                    as ::bitfield::__private::Storage>::Bytes;
+                type InOut = #name;
+                type Output = #name;
+
+                #[inline]
+                fn into_bytes(input: #name) -> Self::Bytes {
+                    let raw = #read;
+                    ::bitfield::__private::narrow(raw)
+                }
+
+                #[inline]
+                fn from_bytes(bytes: Self::Bytes) -> #name {
+                    let mut value = #name::new();
+                    #write;
+                    value
+                }
+            }
+
+            impl #name {
+                #[doc(hidden)]
+                #[inline]
+                #vis const fn __bitfield_into_raw(input: #name) -> u64 {
+                    #read
+                }
+            }
+        })
+    }
+
+    /// 为写了`#[derive(Debug)]`、`#[derive(PartialEq)]`或`#[derive(Hash)]`的位域生成逐个字段的实现
+    /// 没有getter的字段会被忽略
+    /// ```ignore
+    /// impl core::fmt::Debug for MyFourBytes {
+    ///     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    ///         f.debug_struct("MyFourBytes").field("a", &self.get_a())...finish()
+    ///         // 元组结构体为f.debug_tuple("Pair").field(&self.get_0())...finish()
+    ///     }
+    /// }
+    /// impl core::cmp::PartialEq for MyFourBytes {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         true && self.get_a() == other.get_a() && ...
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_derives(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        let name = config.name;
+        // 没有getter的保留字段与只写字段不参与输出、比较与哈希
+        let fields = config
+            .fields
+            .iter()
+            .filter(|field| field.has_getter())
+            .collect::<Vec<_>>();
+        // 数组字段的值为由各元素组成的数组，`#[bitfield(atomic)]`以`Relaxed`读取
+        let order = config
+            .args
+            .atomic
+            .then(|| quote!(core::sync::atomic::Ordering::Relaxed));
+        let value = |field: &FieldConfig, receiver: TokenStream2| {
+            let getter = format_ident!("get_{}", field.ident);
+            match field.len {
+                Some(len) => quote! {
+                    core::array::from_fn::<_, { #len }, _>(|index| #receiver.#getter(index, #order))
+                },
+                None => quote! {
+                    #receiver.#getter(#order)
+                },
+            }
+        };
+        let values = fields
+            .iter()
+            .map(|field| value(field, quote!(self)))
+            .collect::<Vec<_>>();
+        let other_values = fields.iter().map(|field| value(field, quote!(other)));
+        let mut ret = TokenStream2::new();
+        if config.derives.debug {
+            let name_literal = name.to_string();
+            let tuple = config.is_tuple();
+            let fields = fields.iter().zip(&values).map(|(field, value)| {
+                // 元组结构体按位置输出，不带字段名
+                let field_name = (!tuple).then(|| {
+                    let field_name = field.ident.to_string();
+                    quote!(#field_name,)
+                });
+                match field.debug_format {
+                    Some(ref format) => quote! {
+                        .field(#field_name &format_args!(#format, #value))
+                    },
+                    None => quote! {
+                        .field(#field_name &#value)
+                    },
+                }
+            });
+            let debug = if tuple {
+                quote!(debug_tuple)
+            } else {
+                quote!(debug_struct)
+            };
+            ret.extend(quote! {
+                impl core::fmt::Debug for #name {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        f.#debug(#name_literal)
+                            #(#fields)*
+                            .finish()
+                    }
+                }
+            });
+        }
+        if config.derives.partial_eq {
+            ret.extend(quote! {
+                impl core::cmp::PartialEq for #name {
+                    fn eq(&self, other: &Self) -> bool {
+                        true #(&& #values == #other_values)*
+                    }
+                }
+            });
+        }
+        if config.derives.hash {
+            ret.extend(quote! {
+                impl core::hash::Hash for #name {
+                    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                        #(core::hash::Hash::hash(&#values, state);)*
+                    }
+                }
+            });
+        }
+        Ok(ret)
+    }
+
+    /// 为写了`#[derive(Serialize)]`或`#[derive(Deserialize)]`的位域生成serde实现
+    ///
+    /// 人类可读格式中为字段名到值的映射，包含除保留字段外所有有getter的字段；
+    /// 反序列化时所有可读写的字段都必须出现，只读字段会被忽略，只写字段可以省略，省略时保持为零。
+    /// 稀疏枚举字段中无法识别的位序列化为原始整数，反序列化时原样写回。其他格式中为存储的字节
+    pub fn generate_serde(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        let name = config.name;
+        let name_literal = name.to_string();
+        let total_bytes = config.total_bytes();
+        let storage_bytes = config.storage_bytes(quote!(self));
+        let fields = config
+            .fields
+            .iter()
+            .filter(|field| !field.is_reserved() && (field.has_getter() || field.has_setter()))
+            .collect::<Vec<_>>();
+        let field_names = fields
+            .iter()
+            .map(|field| field.ident.unraw())
+            .collect::<Vec<_>>();
+        let mut ret = TokenStream2::new();
+        if config.derives.serialize {
+            let entries = fields.iter().zip(&field_names).filter(|(field, _)| field.has_getter()).map(|(field, field_name)| {
+                let getter = format_ident!("get_{}", field.ident);
+                match field.len {
+                    Some(len) => quote! {
+                        let values = core::array::from_fn::<_, { #len }, _>(|index| self.#getter(index));
+                        map.serialize_entry(
+                            #field_name,
+                            &::bitfield::__private::serde::Seq(
+                                values.iter().map(|value| (&::bitfield::__private::serde::Value(value)).repr()),
+                            ),
+                        )?;
+                    },
+                    None => quote! {
+                        map.serialize_entry(
+                            #field_name,
+                            &(&::bitfield::__private::serde::Value(&self.#getter())).repr(),
+                        )?;
+                    },
+                }
+            });
+            let count = fields.iter().filter(|field| field.has_getter()).count();
+            ret.extend(quote! {
+                impl ::bitfield::__private::serde::Serialize for #name {
+                    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+                    where
+                        S: ::bitfield::__private::serde::Serializer,
+                    {
+                        use ::bitfield::__private::serde::ser::SerializeMap as _;
+                        use ::bitfield::__private::serde::{PlainValue as _, SparseValue as _};
+                        if !serializer.is_human_readable() {
+                            return serializer.serialize_bytes(&#storage_bytes);
+                        }
+                        let mut map = serializer.serialize_map(Some(#count))?;
+                        #(#entries)*
+                        map.end()
+                    }
+                }
+            });
+        }
+        if config.derives.deserialize {
+            let arms = fields.iter().enumerate().map(|(index, field)| {
+                let ty = field.ty;
+                if !field.has_setter() {
+                    return quote! {
+                        #index => {
+                            map.next_value::<::bitfield::__private::serde::de::IgnoredAny>()?;
+                        }
+                    };
+                }
+                let checked_setter = format_ident!("set_{}_checked", field.ident);
+                let bits = field.element_bits();
+                let field_index = config
+                    .fields
+                    .iter()
+                    .position(|candidate| std::ptr::eq(candidate, *field))
+                    .unwrap();
+                let (element, position) = match field.len {
+                    Some(_) => (quote!(index, element), config.element_position(field_index)),
+                    None => (quote!(element), config.field_position(field_index)),
+                };
+                let write = config.write_storage(quote!(value), position, bits.clone(), quote!(raw));
+                let set = quote! {
+                    match element {
+                        ::bitfield::__private::serde::Field::Value(element) => value
+                            .#checked_setter(#element)
+                            .map_err(::bitfield::__private::serde::de::Error::custom)?,
+                        ::bitfield::__private::serde::Field::Raw(raw) => {
+                            if !<#ty as ::bitfield::Specifier>::fits(raw) {
+                                return core::result::Result::Err(
+                                    ::bitfield::__private::serde::de::Error::custom(
+                                        ::bitfield::OutOfBounds::new(raw, #bits),
+                                    ),
+                                );
+                            }
+                            #write;
+                        }
+                    }
+                };
+                let repr = quote! {
+                    (&::bitfield::__private::serde::FieldType::<
+                        <#ty as ::bitfield::Specifier>::Output,
+                        <#ty as ::bitfield::Specifier>::InOut,
+                    >(core::marker::PhantomData))
+                        .repr()
+                };
+                match field.len {
+                    Some(len) => quote! {
+                        #index => {
+                            let values = ::bitfield::__private::serde::next_array::<_, _, _, { #len }>(&mut map, #repr)?;
+                            for (index, element) in values.into_iter().enumerate() {
+                                #set
+                            }
+                        }
+                    },
+                    None => quote! {
+                        #index => {
+                            let element = ::bitfield::__private::serde::next_field(&mut map, #repr)?;
+                            #set
+                        }
+                    },
+                }
+            });
+            let required = fields
+                .iter()
+                .zip(&field_names)
+                .enumerate()
+                .filter(|(_, (field, _))| field.has_getter() && field.has_setter())
+                .map(|(index, (_, field_name))| {
+                    quote! {
+                        if !seen[#index] {
+                            return core::result::Result::Err(
+                                ::bitfield::__private::serde::de::Error::missing_field(#field_name),
+                            );
+                        }
+                    }
+                });
+            let count = fields.len();
+            ret.extend(quote! {
+                impl<'de> ::bitfield::__private::serde::Deserialize<'de> for #name {
+                    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+                    where
+                        D: ::bitfield::__private::serde::Deserializer<'de>,
+                    {
+                        const FIELDS: &[&str] = &[#(#field_names),*];
+
+                        struct Visitor;
+
+                        impl<'de> ::bitfield::__private::serde::de::Visitor<'de> for Visitor {
+                            type Value = #name;
+
+                            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                                f.write_str(concat!("struct ", #name_literal))
+                            }
+
+                            fn visit_map<A>(self, mut map: A) -> core::result::Result<#name, A::Error>
+                            where
+                                A: ::bitfield::__private::serde::de::MapAccess<'de>,
+                            {
+                                use ::bitfield::__private::serde::{PlainField as _, SparseField as _};
+                                let mut value = #name::new();
+                                let mut seen = [false; #count];
+                                let identifier = || ::bitfield::__private::serde::Identifier {
+                                    names: FIELDS,
+                                    variant: false,
+                                };
+                                while let Some(index) = map.next_key_seed(identifier())? {
+                                    if seen[index] {
+                                        return core::result::Result::Err(
+                                            ::bitfield::__private::serde::de::Error::duplicate_field(FIELDS[index]),
+                                        );
+                                    }
+                                    seen[index] = true;
+                                    match index {
+                                        #(#arms)*
+                                        _ => unreachable!(),
+                                    }
+                                }
+                                #(#required)*
+                                core::result::Result::Ok(value)
+                            }
+                        }
+
+                        if deserializer.is_human_readable() {
+                            deserializer.deserialize_struct(#name_literal, FIELDS, Visitor)
+                        } else {
+                            ::bitfield::__private::serde::deserialize_bytes::<_, { #total_bytes }>(deserializer)
+                                .map(#name::from_bytes)
+                        }
+                    }
+                }
+            });
+        }
+        Ok(ret)
+    }
+
+    /// 生成以字节数组存储的结构体
+    /// # 原结构体
+    /// ```ignore
+    /// pub struct MyFourBytes {
+    ///     a: B1,
+    ///     b: B3,
+    ///     c: B4,
+    ///     d: B24,
+    /// }
+    /// ```
+    /// ---
+    /// # 生成的结构体
+    /// ```ignore
+    /// #[repr(C)]
+    /// pub struct MyFourBytes {
+    ///     data: [u8; (0 + <B1 as Specifier>::BITS + ...) / 8],
+    /// }
+    /// ```
+    ///
+    /// `#[bitfield(atomic)]`时`data`为能够容纳所有位的最窄原子整数
+    pub fn generate_struct(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        let attrs = &config.attrs;
+        let vis = config.vis;
+        let name = config.name;
+        let (repr, storage) = if config.args.atomic {
+            (quote!(C), config.atomic_type())
+        } else if let Some(ref repr) = config.args.repr {
+            (quote!(transparent), quote!(#repr))
+        } else {
+            let total_bytes = config.total_bytes();
+            (quote!(C), quote!([u8; #total_bytes]))
+        };
+        Ok(quote! {
+            #(#attrs)*
+            #[repr(#repr)]
+            #vis struct #name {
+                data: #storage,
+            }
+        })
+    }
+
+    /// 生成编译期检查
+    /// * 总位数不是8的倍数时无法通过编译；`bytes = N`或`repr = uN`时改为检查字段能否放进存储
+    /// * 字段上`#[bits = N]`与其类型的`Specifier::BITS`不一致时无法通过编译，错误指向`N`
+    /// * 字段上`#[bits(start..end)]`的宽度与其类型的`Specifier::BITS`不一致时无法通过编译，错误指向范围
+    /// * `#[bitfield(atomic)]`的总位数超过64时无法通过编译
+    pub fn generate_checks(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        let total_bits = config.total_bits();
+        let fields_bits = config.fields_bits();
+        // 指定了存储大小时字段之后的位补零，只需检查字段能否放进存储
+        let size_check = if config.args.bytes.is_some() || config.args.repr.is_some() {
+            let message = format!(
+                "the fields of #[bitfield] struct `{}` do not fit in its storage",
+                config.name
+            );
+            quote! {
+                const _: () = assert!(#fields_bits <= #total_bits, #message);
+            }
+        } else {
+            quote! {
+                const _: () = ::bitfield::checks::total_size_is_multiple_of_eight_bits::<
+                    <[(); #total_bits % 8] as ::bitfield::checks::Mod8>::Marker,
+                >();
+            }
+        };
+        let atomic_bits = config.args.atomic.then(|| {
+            let message = format!(
+                "#[bitfield(atomic)] struct `{}` is wider than the 64 bits an atomic integer can hold",
+                config.name
+            );
+            quote! {
+                const _: () = assert!(#total_bits <= 64, #message);
+            }
+        });
+        let declared_bits = config.fields.iter().filter_map(|field| {
+            let lit = field.declared_bits.as_ref()?;
+            let bits = field.bits();
+            Some(quote_spanned! {lit.span()=>
+                const _: [(); #lit] = [(); #bits];
+            })
+        });
+        // 显式布局中范围的宽度与`#[bits = N]`一样需要与类型的位数一致，错误指向范围
+        let range_bits = config.fields.iter().filter_map(|field| {
+            let range = field.range.as_ref()?;
+            let lit = syn::LitInt::new(&(range.end - range.start).to_string(), range.expr.span());
+            let bits = field.bits();
+            Some(quote_spanned! {lit.span()=>
+                const _: [(); #lit] = [(); #bits];
+            })
+        });
+        Ok(quote! {
+            #size_check
+            #(#declared_bits)*
+            #(#range_bits)*
+            #atomic_bits
+        })
+    }
+
+    /// 生成`new`方法、与字节数组相互转换的方法以及每个字段的访问方法
+    /// setter的参数类型为字段类型的`Specifier::InOut`，getter的返回值类型为`Specifier::Output`
+    pub fn generate_accessors(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        if config.args.atomic {
+            return Ok(Self::atomic_accessors(config));
+        }
+        if config.args.repr.is_some() {
+            return Ok(Self::repr_accessors(config));
+        }
+        let name = config.name;
+        let vis = config.vis;
+        let total_bytes = config.total_bytes();
+        let accessors = (0..config.fields.len()).map(|index| {
+            let accessors = Self::field_accessors(config, index);
+            let with = Self::field_with(config, index);
+            quote! {
+                #accessors
+                #with
+            }
+        });
+        Ok(quote! {
+            impl #name {
+                #vis const fn new() -> Self {
+                    Self {
+                        data: [0; #total_bytes],
+                    }
+                }
+
+                /// 直接以字节数组作为存储，不做任何复制或校验
+                #vis const fn from_bytes(bytes: [u8; #total_bytes]) -> Self {
+                    Self { data: bytes }
+                }
+
+                /// 取出存储的字节数组
+                #vis const fn into_bytes(self) -> [u8; #total_bytes] {
+                    self.data
+                }
+
+                /// 以字节数组的形式借用存储
+                #vis const fn as_bytes(&self) -> &[u8; #total_bytes] {
+                    &self.data
+                }
+
+                /// 以字节数组的形式可变借用存储
+                #vis const fn as_mut_bytes(&mut self) -> &mut [u8; #total_bytes] {
+                    &mut self.data
+                }
+
+                #(#accessors)*
+            }
+        })
+    }
+
+    /// 生成`#[bitfield(repr = u32)]`的构造与转换方法、与整数的相互转换以及每个字段的访问方法
+    /// 字节数组与整数之间按`endian`转换，不生成`as_bytes`与`as_mut_bytes`
+    fn repr_accessors(config: &BitfieldConfig) -> TokenStream2 {
+        let name = config.name;
+        let vis = config.vis;
+        let repr = config.args.repr.as_ref().unwrap();
+        let total_bytes = config.total_bytes();
+        let (from, into) = if config.big_endian() {
+            (quote!(from_be_bytes), quote!(to_be_bytes))
+        } else {
+            (quote!(from_le_bytes), quote!(to_le_bytes))
+        };
+        let accessors = (0..config.fields.len()).map(|index| {
+            let accessors = Self::field_accessors(config, index);
+            let with = Self::field_with(config, index);
+            quote! {
+                #accessors
+                #with
+            }
+        });
+        quote! {
+            impl #name {
+                #vis const fn new() -> Self {
+                    Self { data: 0 }
+                }
+
+                /// 以字节数组的内容构造
+                #vis const fn from_bytes(bytes: [u8; #total_bytes]) -> Self {
+                    Self {
+                        data: #repr::#from(bytes),
+                    }
+                }
+
+                /// 取出存储的内容
+                #vis const fn into_bytes(self) -> [u8; #total_bytes] {
+                    self.data.#into()
+                }
+
+                /// 直接以整数作为存储
+                #vis const fn from_bits(bits: #repr) -> Self {
+                    Self { data: bits }
+                }
+
+                /// 取出存储的整数
+                #vis const fn into_bits(self) -> #repr {
+                    self.data
+                }
+
+                #(#accessors)*
+            }
+
+            impl core::convert::From<#repr> for #name {
+                #[inline]
+                fn from(bits: #repr) -> Self {
+                    Self::from_bits(bits)
+                }
+            }
+
+            impl core::convert::From<#name> for #repr {
+                #[inline]
+                fn from(value: #name) -> Self {
+                    value.into_bits()
+                }
+            }
+        }
+    }
+
+    /// 实现`bitfield::Bitfield`，字节数组存储委托给生成的`from_bytes`与`into_bytes`
+    /// 整数存储的`from_bytes`与`into_bytes`按`endian`转换，trait方法则使用本机字节序，因此命名为`*_ne_bytes`
+    /// `#[bitfield(atomic)]`的存储不是字节数组，不实现此trait
+    pub fn generate_bitfield_impl(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        if config.args.atomic {
+            return Ok(TokenStream2::new());
+        }
+        let name = config.name;
+        let total_bytes = config.total_bytes();
+        // Register以本机字节序的整数读写，整数存储不能再按endian转换
+        let (from_bytes, into_bytes) = match config.args.repr {
+            Some(ref repr) => (
+                quote!(Self::from_bits(#repr::from_ne_bytes(bytes))),
+                quote!(self.into_bits().to_ne_bytes()),
+            ),
+            None => (quote!(Self::from_bytes(bytes)), quote!(self.into_bytes())),
+        };
+        Ok(quote! {
+            // SAFETY: 结构体只有一个`[u8; N]`或无符号整数字段，`repr(C)`或`repr(transparent)`保证大小相同
+            unsafe impl ::bitfield::Bitfield for #name {
+                type Bytes = [u8; #total_bytes];
+
+                #[inline]
+                fn from_ne_bytes(bytes: Self::Bytes) -> Self {
+                    #from_bytes
+                }
+
+                #[inline]
+                fn into_ne_bytes(self) -> Self::Bytes {
+                    #into_bytes
+                }
+            }
+        })
+    }
+
+    /// 生成整个位域以及各字段的位数、偏移与掩码常量，名称只由下划线组成的保留字段除外
+    /// 数组字段的常量描述整个数组，另有元素个数`_LEN`
+    /// 掩码为`u64`，不完全位于低64位的字段使用其`_MASK`时无法通过编译
+    /// ```ignore
+    /// impl MyFourBytes {
+    ///     pub const BITS: usize = 32;
+    ///     pub const BYTES: usize = 4;
+    ///     pub const A_OFFSET: usize = 0;
+    ///     pub const A_BITS: usize = 1;
+    ///     pub const A_MASK: u64 = 0b1;
+    ///     ...
+    /// }
+    /// ```
+    pub fn generate_consts(config: &BitfieldConfig) -> syn::Result<TokenStream2> {
+        let name = config.name;
+        let vis = config.vis;
+        let total_bits = config.total_bits();
+        let total_bytes = config.total_bytes();
+        let fields = config
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| !field.is_reserved())
+            .map(|(index, field)| {
+                let prefix = field.ident.const_prefix();
+                let offset = format_ident!("{}_OFFSET", prefix);
+                let bits_const = format_ident!("{}_BITS", prefix);
+                let mask = format_ident!("{}_MASK", prefix);
+                let position = config.field_position(index);
+                let bits = field.bits();
+                // 掩码为u64，不完全位于低64位的字段只在使用其掩码时报错
+                let message = format!(
+                    "`{}::{}` is unavailable because field `{}` does not lie within the low 64 bits",
+                    name, mask, field.ident
+                );
+                let len = field.len.map(|len| {
+                    let len_const = format_ident!("{}_LEN", prefix);
+                    quote! {
+                        #vis const #len_const: usize = #len;
+                    }
+                });
+                quote! {
+                    #len
+                    #vis const #offset: usize = #position;
+                    #vis const #bits_const: usize = #bits;
+                    #vis const #mask: u64 = {
+                        assert!(Self::#offset + Self::#bits_const <= 64, #message);
+                        ::bitfield::__private::mask(Self::#offset, Self::#bits_const)
+                    };
+                }
+            });
+        Ok(quote! {
+            impl #name {
+                #vis const BITS: usize = #total_bits;
+                #vis const BYTES: usize = #total_bytes;
+                #(#fields)*
+            }
+        })
+    }
+
+    /// 生成第`index`个字段的`get_`、`set_`与`set_..._checked`方法，被`#[skip]`跳过的方法不会生成
+    ///
+    /// 数组字段的访问方法多出一个元素序号参数，序号越界时panic，并额外生成遍历所有元素的`iter_`方法
+    /// ```ignore
+    /// pub fn get_a(&self) -> <B1 as Specifier>::Output { ... }
+    /// pub fn set_a(&mut self, value: <B1 as Specifier>::InOut) { ... }
+    /// pub fn set_a_checked(&mut self, value: <B1 as Specifier>::InOut) -> Result<(), OutOfBounds> { ... }
+    /// pub fn get_lanes(&self, index: usize) -> <B4 as Specifier>::Output { ... }
+    /// pub fn iter_lanes(&self) -> impl Iterator<Item = <B4 as Specifier>::Output> + '_ { ... }
+    /// ```
+    fn field_accessors(config: &BitfieldConfig, index: usize) -> TokenStream2 {
+        let vis = config.vis;
+        let field = &config.fields[index];
+        let getter = format_ident!("get_{}", field.ident);
+        let setter = format_ident!("set_{}", field.ident);
+        let checked_setter = format_ident!("set_{}_checked", field.ident);
+        let bits = field.element_bits();
+        let ty = field.ty;
+        let (index_arg, index_check, position) = Self::field_location(config, index);
+        let overflow_check = Self::overflow_check(config, index);
+        let read = config.read_storage(quote!(self), position.clone(), bits.clone());
+        let write = config.write_storage(quote!(self), position, bits.clone(), quote!(raw));
+        let mut ret = TokenStream2::new();
+        if field.has_getter() {
+            ret.extend(quote! {
+                #[track_caller]
+                #vis fn #getter(&self, #index_arg) -> <#ty as ::bitfield::Specifier>::Output {
+                    #index_check
+                    let raw = #read;
+                    <#ty as ::bitfield::Specifier>::from_bytes(::bitfield::__private::narrow(raw))
+                }
+            });
+            if let Some(len) = field.len {
+                let iter = format_ident!("iter_{}", field.ident);
+                ret.extend(quote! {
+                    #vis fn #iter(
+                        &self,
+                    ) -> impl Iterator<Item = <#ty as ::bitfield::Specifier>::Output> + '_ {
+                        (0..#len).map(move |index| self.#getter(index))
+                    }
+                });
+            }
+        }
+        if !field.has_setter() {
+            return ret;
+        }
+        ret.extend(quote! {
+            #[track_caller]
+            #vis fn #setter(&mut self, #index_arg value: <#ty as ::bitfield::Specifier>::InOut) {
+                #index_check
+                let raw: u64 = <#ty as ::bitfield::Specifier>::into_bytes(value).into();
+                #overflow_check
+                #write
+            }
+
+            #[track_caller]
+            #vis fn #checked_setter(
+                &mut self,
+                #index_arg
+                value: <#ty as ::bitfield::Specifier>::InOut,
+            ) -> core::result::Result<(), ::bitfield::OutOfBounds> {
+                #index_check
+                let raw: u64 = <#ty as ::bitfield::Specifier>::into_bytes(value).into();
+                if !<#ty as ::bitfield::Specifier>::fits(raw) {
+                    return core::result::Result::Err(::bitfield::OutOfBounds::new(raw, #bits));
+                }
+                #write;
+                core::result::Result::Ok(())
+            }
+        });
+        ret
+    }
+
+    /// 生成`#[bitfield(atomic)]`的构造、转换与访问方法
+    ///
+    /// 访问方法都通过`&self`工作并接收`Ordering`，setter是比较并交换的循环，
+    /// 另有`fetch_update_`方法，语义同标准库原子整数的`fetch_update`
+    /// ```ignore
+    /// pub fn get_a(&self, order: Ordering) -> <B1 as Specifier>::Output { ... }
+    /// pub fn set_a(&self, value: <B1 as Specifier>::InOut, order: Ordering) { ... }
+    /// pub fn fetch_update_a<F>(&self, set_order: Ordering, fetch_order: Ordering, f: F)
+    ///     -> Result<<B1 as Specifier>::Output, <B1 as Specifier>::Output>
+    /// where
+    ///     F: FnMut(<B1 as Specifier>::Output) -> Option<<B1 as Specifier>::InOut> { ... }
+    /// ```
+    fn atomic_accessors(config: &BitfieldConfig) -> TokenStream2 {
+        let name = config.name;
+        let vis = config.vis;
+        let total_bits = config.total_bits();
+        let total_bytes = config.total_bytes();
+        let big_endian = config.big_endian();
+        let atomic = config.atomic_type();
+        let accessors =
+            (0..config.fields.len()).map(|index| Self::atomic_field_accessors(config, index));
+        quote! {
+            impl #name {
+                #vis fn new() -> Self {
+                    Self {
+                        data: <#atomic as ::bitfield::__private::Atomic>::new(0),
+                    }
+                }
+
+                /// 以字节数组的内容构造
+                #vis fn from_bytes(bytes: [u8; #total_bytes]) -> Self {
+                    let raw = ::bitfield::__private::read(&bytes, 0, #total_bits, #big_endian);
+                    Self {
+                        data: <#atomic as ::bitfield::__private::Atomic>::new(raw),
+                    }
+                }
+
+                /// 取出存储的内容
+                #vis fn into_bytes(self) -> [u8; #total_bytes] {
+                    let raw = <#atomic as ::bitfield::__private::Atomic>::into_inner(self.data);
+                    let mut bytes = [0; #total_bytes];
+                    ::bitfield::__private::write(&mut bytes, 0, #total_bits, #big_endian, raw);
+                    bytes
+                }
+
+                /// 以`order`读取整个存储
+                #vis fn load_bytes(&self, order: core::sync::atomic::Ordering) -> [u8; #total_bytes] {
+                    let raw = <#atomic as ::bitfield::__private::Atomic>::load(&self.data, order);
+                    let mut bytes = [0; #total_bytes];
+                    ::bitfield::__private::write(&mut bytes, 0, #total_bits, #big_endian, raw);
+                    bytes
+                }
+
+                #(#accessors)*
+            }
+        }
+    }
+
+    /// 生成`#[bitfield(atomic)]`第`index`个字段的访问方法，位置与掩码的计算与普通位域相同
+    fn atomic_field_accessors(config: &BitfieldConfig, index: usize) -> TokenStream2 {
+        let vis = config.vis;
+        let field = &config.fields[index];
+        let getter = format_ident!("get_{}", field.ident);
+        let setter = format_ident!("set_{}", field.ident);
+        let checked_setter = format_ident!("set_{}_checked", field.ident);
+        let fetch_update = format_ident!("fetch_update_{}", field.ident);
+        let with = format_ident!("with_{}", field.ident);
+        let bits = field.element_bits();
+        let ty = field.ty;
+        let atomic = config.atomic_type();
+        let (index_arg, index_check, position) = Self::field_location(config, index);
+        let index_value = field.len.map(|_| quote!(index,));
+        let overflow_check = Self::overflow_check(config, index);
+        let mut ret = TokenStream2::new();
+        if field.has_getter() {
+            ret.extend(quote! {
+                #[track_caller]
+                #vis fn #getter(
+                    &self,
+                    #index_arg
+                    order: core::sync::atomic::Ordering,
+                ) -> <#ty as ::bitfield::Specifier>::Output {
+                    #index_check
+                    let mask = ::bitfield::__private::mask(#position, #bits);
+                    let word = <#atomic as ::bitfield::__private::Atomic>::load(&self.data, order);
+                    let raw = (word & mask) >> #position;
+                    <#ty as ::bitfield::Specifier>::from_bytes(::bitfield::__private::narrow(raw))
+                }
+            });
+        }
+        if !field.has_setter() {
+            return ret;
+        }
+        ret.extend(quote! {
+            #[track_caller]
+            #vis fn #setter(
+                &self,
+                #index_arg
+                value: <#ty as ::bitfield::Specifier>::InOut,
+                order: core::sync::atomic::Ordering,
+            ) {
+                #index_check
+                let raw: u64 = <#ty as ::bitfield::Specifier>::into_bytes(value).into();
+                #overflow_check
+                let mask = ::bitfield::__private::mask(#position, #bits);
+                let _ = <#atomic as ::bitfield::__private::Atomic>::fetch_update(
+                    &self.data,
+                    order,
+                    ::bitfield::__private::failure_ordering(order),
+                    |word| Some((word & !mask) | ((raw << #position) & mask)),
+                );
+            }
+
+            #[track_caller]
+            #vis fn #checked_setter(
+                &self,
+                #index_arg
+                value: <#ty as ::bitfield::Specifier>::InOut,
+                order: core::sync::atomic::Ordering,
+            ) -> core::result::Result<(), ::bitfield::OutOfBounds> {
+                #index_check
+                let raw: u64 = <#ty as ::bitfield::Specifier>::into_bytes(value).into();
+                if !<#ty as ::bitfield::Specifier>::fits(raw) {
+                    return core::result::Result::Err(::bitfield::OutOfBounds::new(raw, #bits));
+                }
+                let mask = ::bitfield::__private::mask(#position, #bits);
+                let _ = <#atomic as ::bitfield::__private::Atomic>::fetch_update(
+                    &self.data,
+                    order,
+                    ::bitfield::__private::failure_ordering(order),
+                    |word| Some((word & !mask) | ((raw << #position) & mask)),
+                );
+                core::result::Result::Ok(())
+            }
+
+            #[track_caller]
+            #vis fn #with(self, #index_arg value: <#ty as ::bitfield::Specifier>::InOut) -> Self {
+                self.#setter(#index_value value, core::sync::atomic::Ordering::Relaxed);
+                self
+            }
+        });
+        if field.has_getter() {
+            ret.extend(quote! {
+                #[track_caller]
+                #vis fn #fetch_update<F>(
+                    &self,
+                    #index_arg
+                    set_order: core::sync::atomic::Ordering,
+                    fetch_order: core::sync::atomic::Ordering,
+                    mut f: F,
+                ) -> core::result::Result<
+                    <#ty as ::bitfield::Specifier>::Output,
+                    <#ty as ::bitfield::Specifier>::Output,
+                >
+                where
+                    F: FnMut(<#ty as ::bitfield::Specifier>::Output) -> Option<<#ty as ::bitfield::Specifier>::InOut>,
+                {
+                    #index_check
+                    let mask = ::bitfield::__private::mask(#position, #bits);
+                    let decode = |word: u64| {
+                        let raw = (word & mask) >> #position;
+                        <#ty as ::bitfield::Specifier>::from_bytes(::bitfield::__private::narrow(raw))
+                    };
+                    <#atomic as ::bitfield::__private::Atomic>::fetch_update(
+                        &self.data,
+                        set_order,
+                        fetch_order,
+                        |word| {
+                            let value = f(decode(word))?;
+                            let raw: u64 = <#ty as ::bitfield::Specifier>::into_bytes(value).into();
+                            #overflow_check
+                            Some((word & !mask) | ((raw << #position) & mask))
+                        },
+                    )
+                    .map(decode)
+                    .map_err(decode)
+                }
+            });
+        }
+        ret
+    }
+
+    /// 第`index`个字段的序号参数、序号检查与最低位在存储中的位置
+    ///
+    /// 数组字段的访问方法多出`index: usize`参数，位置为第`index`个元素的位置
+    fn field_location(
+        config: &BitfieldConfig,
+        index: usize,
+    ) -> (TokenStream2, TokenStream2, TokenStream2) {
+        let field = &config.fields[index];
+        let field_name = field.ident.to_string();
+        match field.len {
+            Some(len) => (
+                quote!(index: usize,),
+                quote! {
+                    assert!(
+                        index < #len,
+                        "index {} is out of range for field `{}` of length {}",
+                        index,
+                        #field_name,
+                        #len,
+                    );
+                },
+                config.element_position(index),
+            ),
+            None => (quote!(), quote!(), config.field_position(index)),
+        }
+    }
+
+    /// 按溢出处理方式以`Specifier::fits`检查变量`raw`能否写入第`index`个字段（数组字段为单个元素）
+    fn overflow_check(config: &BitfieldConfig, index: usize) -> TokenStream2 {
+        let field = &config.fields[index];
+        let field_name = field.ident.to_string();
+        let bits = field.element_bits();
+        let ty = field.ty;
+        match config.args.overflow {
+            Overflow::Panic => quote! {
+                assert!(
+                    <#ty as ::bitfield::Specifier>::fits(raw),
+                    "value {:#x} does not fit in the {} bits of field `{}`",
+                    raw,
+                    #bits,
+                    #field_name,
+                );
+            },
+            Overflow::DebugAssert => quote! {
+                debug_assert!(
+                    <#ty as ::bitfield::Specifier>::fits(raw),
+                    "value {:#x} does not fit in the {} bits of field `{}`",
+                    raw,
+                    #bits,
+                    #field_name,
+                );
+            },
+            Overflow::Truncate => quote!(),
+        }
+    }
+
+    /// 生成第`index`个字段的`with_`方法，它消耗并返回`self`，便于链式构造
+    ///
+    /// 字段类型（数组字段为元素类型）为`bool`、`B1`到`B64`或标有`#[const_with]`时，
+    /// 转换不调用trait方法而调用类型上的`__bitfield_into_raw`，生成的是`const fn`，
+    /// 可以用于`const`与`static`的初始化；其他类型的`with_`委托给`set_`
+    /// ```ignore
+    /// pub const fn with_a(mut self, value: <B1 as Specifier>::InOut) -> Self { ... }
+    /// pub fn with_version(mut self, value: <Version as Specifier>::InOut) -> Self { ... }
+    /// pub const fn with_lanes(mut self, index: usize, value: <B4 as Specifier>::InOut) -> Self { ... }
+    /// ```
+    fn field_with(config: &BitfieldConfig, index: usize) -> TokenStream2 {
+        let vis = config.vis;
+        let field = &config.fields[index];
+        let with = format_ident!("with_{}", field.ident);
+        let ty = field.ty;
+        if !field.has_setter() {
+            return TokenStream2::new();
+        }
+        let (index_arg, index_value) = match field.len {
+            Some(_) => (quote!(index: usize,), quote!(index,)),
+            None => (quote!(), quote!()),
+        };
+        let into_raw = match utils::primitive_specifier(ty) {
+            Some(utils::Primitive::Bool) => quote!(value as u64),
+            Some(utils::Primitive::Bits) => quote!(<#ty>::__bitfield_into_raw(value)),
+            None if field.const_with => quote_spanned! {ty.span()=>
+                <#ty>::__bitfield_into_raw(value)
+            },
+            None => {
+                let setter = format_ident!("set_{}", field.ident);
+                return quote! {
+                    #[track_caller]
+                    #vis fn #with(mut self, #index_arg value: <#ty as ::bitfield::Specifier>::InOut) -> Self {
+                        self.#setter(#index_value value);
+                        self
+                    }
+                };
+            }
+        };
+
+        let bits = field.element_bits();
+        // const fn中的panic只能使用字面量信息
+        let (index_check, position) = match field.len {
+            Some(len) => {
+                let message = format!("index is out of range for field `{}`", field.ident);
+                (
+                    quote! {
+                        assert!(index < #len, #message);
+                    },
+                    config.element_position(index),
+                )
+            }
+            None => (quote!(), config.field_position(index)),
+        };
+        let write = config.write_storage(quote!(self), position, bits.clone(), quote!(raw));
+        let message = format!("value does not fit in the bits of field `{}`", field.ident);
+        let overflow_check = match config.args.overflow {
+            Overflow::Panic => quote! {
+                assert!(::bitfield::__private::fits(raw, #bits), #message);
+            },
+            Overflow::DebugAssert => quote! {
+                debug_assert!(::bitfield::__private::fits(raw, #bits), #message);
+            },
+            Overflow::Truncate => quote!(),
+        };
+        quote! {
+            #[track_caller]
+            #vis const fn #with(mut self, #index_arg value: <#ty as ::bitfield::Specifier>::InOut) -> Self {
+                #index_check
+                let raw = #into_raw;
+                #overflow_check
+                #write;
+                self
+            }
+        }
+    }
+}
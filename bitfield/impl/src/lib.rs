@@ -1,9 +1,157 @@
+//! `bitfield`所使用的过程宏
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::parse_macro_input;
 
+use args::BitfieldArgs;
+use generator::Generator;
+use model::BitfieldConfig;
+use specifier::SpecifierConfig;
+
+mod args;
+mod config;
+mod generator;
+mod model;
+mod specifier;
+mod utils;
+
+/// 将结构体的各字段紧凑地存放到`[u8; N]`中，并为每个字段生成`get_`与`set_`方法
+/// 元组结构体的字段以序号命名，第0个字段生成`get_0`、`set_0`与`with_0`，常量为`_0_OFFSET`这样以下划线开头的名称
+/// 例如
+/// ```ignore
+/// use bitfield::*;
+///
+/// #[bitfield]
+/// pub struct MyFourBytes {
+///     a: B1,
+///     b: B3,
+///     c: B4,
+///     d: B24,
+/// }
+/// ```
+/// 则生成
+/// ```ignore
+/// #[repr(C)]
+/// pub struct MyFourBytes {
+///     data: [u8; 4],
+/// }
+/// impl MyFourBytes {
+///     pub const fn new() -> Self { ... }
+///     pub const fn from_bytes(bytes: [u8; 4]) -> Self { ... }
+///     pub const fn into_bytes(self) -> [u8; 4] { ... }
+///     pub fn get_a(&self) -> u8 { ... }
+///     pub fn set_a(&mut self, value: u8) { ... }
+///     pub fn set_a_checked(&mut self, value: u8) -> Result<(), OutOfBounds> { ... }
+///     pub const fn with_a(self, value: u8) -> Self { ... }
+///     ...
+/// }
+/// ```
+/// `bool`与`B1`到`B64`字段（以及它们的数组）的`with_`是`const fn`，可以用于`const`与`static`的初始化。
+/// 宏看不到其他字段类型的定义，`#[derive(BitfieldSpecifier)]`的枚举或嵌套的位域字段需要标上`#[const_with]`，
+/// 例如`const CFG: Reg = Reg::new().with_enable(true).with_mode(Mode::Fast);`要求`mode`字段标有`#[const_with]`。
+/// 手写`Specifier`实现的类型只能通过trait方法转换，它们的`with_`是委托给`set_`的普通方法。
+/// 名为`B1`到`B64`的类型总被当作`bitfield`中的同名类型，自定义的同名类型需要写成`self::B8`这样的路径
+///
+/// 字段也可以是`[B4; 8]`这样的数组，此时访问方法多出一个元素序号参数，
+/// 并生成遍历所有元素的`iter_`方法
+///
+/// 字段默认依次紧接排列；也可以在每个字段上用`#[bits(12..16)]`或`#[bits(3..=7)]`指定位范围，
+/// 范围按`bit_order`的位序号计数，不能重叠，并且必须从第0位开始连续覆盖整个存储，
+/// 空出的位需要声明为`#[skip]`字段。范围的宽度与`#[bits = N]`一样会和字段类型的位数比较
+///
+/// 同时生成`BITS`、`BYTES`以及每个字段的`A_OFFSET`、`A_BITS`与`A_MASK`常量，
+/// 偏移与掩码都以把整个存储看作一个整数时的位序号计算
+///
+/// 参数
+/// * `bit_order = "lsb0" | "msb0"`：第一个字段位于存储的最低位还是最高位，默认`lsb0`
+/// * `endian = "little" | "big"`：存储的第一个字节存放最低还是最高的8位，默认`little`
+/// * `overflow = "panic" | "truncate" | "debug_assert"`：`set_`收到超出字段位数的值时panic、
+///   丢弃超出的高位或仅在debug构建中panic，默认`panic`。`set_..._checked`总是返回错误
+/// * `atomic`：以能够容纳所有位的最窄原子整数存储，总位数不能超过64。访问方法通过`&self`工作并接收
+///   `Ordering`，setter是比较并交换的循环，另有`fetch_update_`方法；不支持`as_bytes`、
+///   `bitfield::Register`以及`BitfieldSpecifier`、`PartialEq`与`Hash`的derive
+/// * `bytes = N`：存储固定为`N`个字节，字段不必凑满8的倍数位，其后的位补零；字段放不下时无法通过编译
+/// * `repr = u8 | u16 | u32 | u64`：以该整数而不是字节数组存储，并生成`from_bits`、`into_bits`以及与该整数的
+///   `From`转换；不生成`as_bytes`与`as_mut_bytes`。不能与`bytes`和`atomic`同时使用
+///
+/// 结构体同时实现`bitfield::Bitfield`，可以通过`bitfield::Register`以volatile方式读写设备内存
+///
+/// 在`#[bitfield]`之后写`#[derive(BitfieldSpecifier)]`会为结构体实现`bitfield::Specifier`，
+/// 使它可以作为其他位域的字段，此时总位数不能超过64
+///
+/// 写在`#[bitfield]`之后的`Debug`、`CustomDebug`、`PartialEq`与`Hash`同样由`#[bitfield]`接管，
+/// 生成的实现按字段的getter逐个输出、比较或计算哈希，而不是针对存储用的字节数组。
+/// 字段上的`#[debug = "..."]`可以指定该字段的输出格式
+///
+/// 开启`serde` feature后，`Serialize`与`Deserialize`也由`#[bitfield]`接管：人类可读格式中为字段名到值的映射，
+/// 其他格式中为存储的字节。序列化的结果总能反序列化回来：稀疏枚举中无法识别的位写为原始整数，
+/// 只写字段不会被序列化，反序列化时可以省略
+///
+/// 保留位可以写成`#[skip]`或命名为`__`，它们占据位数但不生成访问方法；
+/// `#[skip(getters)]`与`#[skip(setters)]`分别使字段只写或只读
 #[proc_macro_attribute]
 pub fn bitfield(args: TokenStream, input: TokenStream) -> TokenStream {
-    let _ = args;
-    let _ = input;
+    let args = parse_macro_input!(args as BitfieldArgs);
+    let ast = parse_macro_input!(input as syn::ItemStruct);
+    let config = match BitfieldConfig::new(args, &ast) {
+        Ok(config) => config,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    let mut ret = TokenStream2::new();
+    let mut append = |f: fn(&BitfieldConfig) -> syn::Result<TokenStream2>| {
+        f(&config)
+            .unwrap_or_else(syn::Error::into_compile_error)
+            .to_tokens(&mut ret)
+    };
+    // 生成存储结构体
+    append(Generator::generate_struct);
+    // 生成编译期检查
+    append(Generator::generate_checks);
+    // 生成各字段的访问方法
+    append(Generator::generate_accessors);
+    // 生成位数、偏移与掩码常量
+    append(Generator::generate_consts);
+    // 实现bitfield::Bitfield，使结构体可以用于bitfield::Register
+    append(Generator::generate_bitfield_impl);
+    // 生成嵌套所需的Specifier实现
+    append(Generator::generate_specifier);
+    // 生成由#[bitfield]接管的derive
+    append(Generator::generate_derives);
+    // 生成由#[bitfield]接管的serde derive
+    append(Generator::generate_serde);
+
+    ret.into()
+}
 
-    unimplemented!()
+/// 为无字段枚举实现`bitfield::Specifier`，使其可以作为位域成员
+/// 枚举的变体数量必须是2的幂，所占位数为`log2(变体数量)`，每个判别值都必须在`0..2^BITS`范围内
+/// ```ignore
+/// #[derive(BitfieldSpecifier)]
+/// pub enum TriggerMode {
+///     Edge = 0,
+///     Level = 1,
+/// }
+/// ```
+/// 也可以用`#[bits = N]`指定位数，此时变体数量不必是2的幂，
+/// 变体少于`2^N`个时getter返回`Result<T, bitfield::Unrecognized>`
+/// ```ignore
+/// #[derive(BitfieldSpecifier)]
+/// #[bits = 4]
+/// pub enum SmallPrime {
+///     Two = 0b0010,
+///     Three = 0b0011,
+///     Five = 0b0101,
+/// }
+/// ```
+///
+/// 开启`serde` feature后，写上`#[specifier(serde)]`会为枚举实现以变体名表示的`Serialize`与`Deserialize`，
+/// 此时不要再为它写serde的derive；不写该标签时不生成任何serde实现
+#[proc_macro_derive(BitfieldSpecifier, attributes(bits, specifier))]
+pub fn derive_bitfield_specifier(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    SpecifierConfig::try_from(&ast)
+        .and_then(|config| config.generate_specifier())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
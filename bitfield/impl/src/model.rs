@@ -0,0 +1,542 @@
+//! `#[bitfield]`所解析出的结构体信息
+
+use std::fmt;
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, IdentFragment, ToTokens};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+use crate::args::{BitOrder, BitfieldArgs, Endian};
+use crate::{config, utils};
+
+/// 所拿到的位域配置
+/// 位域结构体可以是具名字段或元组结构体
+pub(crate) struct BitfieldConfig<'a> {
+    /// `#[bitfield(...)]`中的参数
+    pub args: BitfieldArgs,
+    /// 结构体上除`#[bitfield]`以外的属性，其中由`#[bitfield]`接管的derive已被移除
+    pub attrs: Vec<syn::Attribute>,
+    /// 结构体访问属性
+    pub vis: &'a syn::Visibility,
+    /// 结构体名称
+    pub name: &'a syn::Ident,
+    /// 位域字段，按声明顺序排列
+    pub fields: Vec<FieldConfig<'a>>,
+    /// 由`#[bitfield]`接管的derive
+    pub derives: Derives,
+}
+
+/// 写在`#[bitfield]`结构体上、由`#[bitfield]`自己生成实现的derive
+/// 这些derive若交给编译器处理，只能看到存储用的字节数组
+#[derive(Default)]
+pub(crate) struct Derives {
+    /// `#[derive(BitfieldSpecifier)]`，使结构体可以嵌套在其他位域中
+    pub specifier: bool,
+    /// `#[derive(Debug)]`或`#[derive(CustomDebug)]`，逐个字段输出解码后的值
+    pub debug: bool,
+    /// `#[derive(PartialEq)]`，逐个字段比较
+    pub partial_eq: bool,
+    /// `#[derive(Hash)]`，逐个字段计算哈希
+    pub hash: bool,
+    /// `#[derive(Serialize)]`
+    pub serialize: bool,
+    /// `#[derive(Deserialize)]`
+    pub deserialize: bool,
+}
+
+/// 位域中的单个字段
+pub(crate) struct FieldConfig<'a> {
+    /// 字段名，元组结构体为字段序号
+    pub ident: FieldName<'a>,
+    /// 字段类型，必须实现`bitfield::Specifier`；数组字段为元素类型
+    pub ty: &'a syn::Type,
+    /// 数组字段`[T; N]`的长度`N`
+    pub len: Option<&'a syn::Expr>,
+    /// `#[bits = N]`中声明的位数，仅用于编译期校验
+    pub declared_bits: Option<syn::LitInt>,
+    /// `#[bits(start..end)]`中声明的位范围
+    pub range: Option<BitRange>,
+    /// `#[debug = "..."]`中声明的格式，用于生成的`Debug`实现
+    pub debug_format: Option<syn::LitStr>,
+    /// `#[skip]`或`#[skip(getters)]`，不生成getter
+    pub skip_getters: bool,
+    /// `#[skip]`或`#[skip(setters)]`，不生成setter
+    pub skip_setters: bool,
+    /// `#[const_with]`，通过类型上由derive生成的`const fn`转换，`with_`为`const fn`
+    pub const_with: bool,
+}
+
+/// 字段名或元组结构体的字段序号，用于拼出访问方法与常量的名称
+///
+/// 具名字段`a`生成`get_a`与`A_OFFSET`，元组结构体的第0个字段生成`get_0`与`_0_OFFSET`
+pub(crate) enum FieldName<'a> {
+    /// 具名字段
+    Named(&'a syn::Ident),
+    /// 元组结构体的字段
+    Unnamed(syn::Index),
+}
+
+impl FieldName<'_> {
+    /// 常量名称的前缀，例如`A`或`_0`
+    pub fn const_prefix(&self) -> String {
+        match self {
+            FieldName::Named(ident) => ident.unraw().to_string().to_uppercase(),
+            FieldName::Unnamed(index) => format!("_{}", index.index),
+        }
+    }
+
+    /// 去掉`r#`的名称，用于序列化
+    pub fn unraw(&self) -> String {
+        match self {
+            FieldName::Named(ident) => ident.unraw().to_string(),
+            FieldName::Unnamed(index) => index.index.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FieldName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldName::Named(ident) => fmt::Display::fmt(ident, f),
+            FieldName::Unnamed(index) => fmt::Display::fmt(&index.index, f),
+        }
+    }
+}
+
+impl IdentFragment for FieldName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldName::Named(ident) => IdentFragment::fmt(*ident, f),
+            FieldName::Unnamed(index) => IdentFragment::fmt(index, f),
+        }
+    }
+
+    fn span(&self) -> Option<proc_macro2::Span> {
+        match self {
+            FieldName::Named(ident) => IdentFragment::span(*ident),
+            FieldName::Unnamed(index) => IdentFragment::span(index),
+        }
+    }
+}
+
+impl ToTokens for FieldName<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            FieldName::Named(ident) => ident.to_tokens(tokens),
+            FieldName::Unnamed(index) => index.to_tokens(tokens),
+        }
+    }
+}
+
+/// 字段上`#[bits(start..end)]`声明的位范围，按`bit_order`的位序号计数
+pub(crate) struct BitRange {
+    /// 标签中的范围，用于报错
+    pub expr: syn::ExprRange,
+    /// 起始位
+    pub start: usize,
+    /// 结束位，不包含
+    pub end: usize,
+}
+
+impl<'a> BitfieldConfig<'a> {
+    /// 生成对`#[bitfield]`参数与结构体的解析结果
+    pub fn new(args: BitfieldArgs, input: &'a syn::ItemStruct) -> syn::Result<Self> {
+        if !input.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &input.generics,
+                "#[bitfield] does not support generic structs",
+            ));
+        }
+        let fields: Vec<_> = match input.fields {
+            syn::Fields::Unit => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "#[bitfield] expected a struct with fields",
+                ))
+            }
+            ref fields => fields
+                .iter()
+                .enumerate()
+                .map(FieldConfig::try_from)
+                .collect::<syn::Result<_>>()?,
+        };
+        check_ranges(&fields)?;
+
+        let mut attrs = input.attrs.clone();
+        let derives = Derives {
+            specifier: utils::take_derive(&mut attrs, config::SPECIFIER_DERIVE)?,
+            debug: utils::take_derive(&mut attrs, config::DEBUG_DERIVE)?
+                | utils::take_derive(&mut attrs, config::CUSTOM_DEBUG_DERIVE)?,
+            partial_eq: utils::take_derive(&mut attrs, config::PARTIAL_EQ_DERIVE)?,
+            hash: utils::take_derive(&mut attrs, config::HASH_DERIVE)?,
+            serialize: utils::take_derive(&mut attrs, config::SERIALIZE_DERIVE)?,
+            deserialize: utils::take_derive(&mut attrs, config::DESERIALIZE_DERIVE)?,
+        };
+        if (derives.serialize || derives.deserialize) && !cfg!(feature = "serde") {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "derive(Serialize) and derive(Deserialize) on a #[bitfield] struct require the `serde` feature of bitfield",
+            ));
+        }
+
+        if args.atomic {
+            let unsupported = [
+                (derives.specifier, config::SPECIFIER_DERIVE),
+                (derives.partial_eq, config::PARTIAL_EQ_DERIVE),
+                (derives.hash, config::HASH_DERIVE),
+                (derives.serialize, config::SERIALIZE_DERIVE),
+                (derives.deserialize, config::DESERIALIZE_DERIVE),
+            ];
+            if let Some((_, derive)) = unsupported.iter().find(|(found, _)| *found) {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    format!("#[bitfield(atomic)] does not support derive({})", derive),
+                ));
+            }
+        }
+
+        Ok(Self {
+            args,
+            attrs,
+            vis: &input.vis,
+            name: &input.ident,
+            fields,
+            derives,
+        })
+    }
+}
+
+impl<'a> TryFrom<(usize, &'a syn::Field)> for FieldConfig<'a> {
+    type Error = syn::Error;
+
+    fn try_from((index, field): (usize, &'a syn::Field)) -> Result<Self, Self::Error> {
+        let ident = match field.ident {
+            Some(ref ident) => FieldName::Named(ident),
+            None => FieldName::Unnamed(syn::Index {
+                index: index as u32,
+                span: field.ty.span(),
+            }),
+        };
+        let mut declared_bits = None;
+        let mut range = None;
+        let mut debug_format = None;
+        let reserved = is_reserved(&ident);
+        let (mut skip_getters, mut skip_setters) = (reserved, reserved);
+        let mut const_with = false;
+        for attr in &field.attrs {
+            if attr.path().is_ident(config::BITS_ATTR) {
+                if let syn::Meta::List(_) = attr.meta {
+                    let (expr, start, end) = utils::parse_bits_range_attr(attr)?;
+                    range = Some(BitRange { expr, start, end });
+                } else {
+                    declared_bits = Some(utils::parse_bits_attr(attr)?);
+                }
+            } else if attr.path().is_ident(config::DEBUG_ATTR) {
+                debug_format = Some(utils::parse_debug_attr(attr)?);
+            } else if attr.path().is_ident(config::SKIP_ATTR) {
+                let (getters, setters) = parse_skip_attr(attr)?;
+                skip_getters |= getters;
+                skip_setters |= setters;
+            } else if attr.path().is_ident(config::CONST_WITH_ATTR) {
+                attr.meta.require_path_only()?;
+                const_with = true;
+            }
+        }
+
+        let (ty, len) = match field.ty {
+            syn::Type::Array(ref array) => (&*array.elem, Some(&array.len)),
+            ref ty => (ty, None),
+        };
+
+        Ok(Self {
+            ident,
+            ty,
+            len,
+            declared_bits,
+            range,
+            debug_format,
+            skip_getters,
+            skip_setters,
+            const_with,
+        })
+    }
+}
+
+/// 检查显式布局：要么所有字段都声明`#[bits(start..end)]`，要么都不声明；
+/// 声明时各范围不能重叠，并且必须从第0位开始连续覆盖整个存储
+fn check_ranges(fields: &[FieldConfig]) -> syn::Result<()> {
+    if fields.iter().all(|field| field.range.is_none()) {
+        return Ok(());
+    }
+    let mut ranges = Vec::with_capacity(fields.len());
+    for field in fields {
+        match field.range {
+            Some(ref range) => ranges.push((range, &field.ident)),
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    format!(
+                        "field `{}` needs a #[bits(start..end)] range because other fields of this struct have one",
+                        field.ident
+                    ),
+                ))
+            }
+        }
+    }
+    ranges.sort_by_key(|(range, _)| range.start);
+    let mut covered = 0;
+    let mut previous = None;
+    for (range, ident) in ranges {
+        if range.start < covered {
+            return Err(syn::Error::new_spanned(
+                &range.expr,
+                format!(
+                    "bits of field `{}` overlap field `{}`",
+                    ident,
+                    previous.unwrap()
+                ),
+            ));
+        }
+        if range.start > covered {
+            return Err(syn::Error::new_spanned(
+                &range.expr,
+                format!(
+                    "bits {}..{} are not covered by any field, declare a #[skip] field for them",
+                    covered, range.start
+                ),
+            ));
+        }
+        covered = range.end;
+        previous = Some(ident);
+    }
+    Ok(())
+}
+
+/// 名为`__`、`___`等的字段是保留位
+fn is_reserved(ident: &FieldName) -> bool {
+    match ident {
+        FieldName::Named(ident) => ident.to_string().bytes().all(|b| b == b'_'),
+        FieldName::Unnamed(_) => false,
+    }
+}
+
+/// 解析`#[skip]`、`#[skip(getters)]`与`#[skip(setters)]`，返回是否跳过getter与setter
+fn parse_skip_attr(attr: &syn::Attribute) -> syn::Result<(bool, bool)> {
+    if let syn::Meta::Path(_) = attr.meta {
+        return Ok((true, true));
+    }
+    let (mut getters, mut setters) = (false, false);
+    let idents =
+        attr.parse_args_with(Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated)?;
+    for ident in idents {
+        if ident == config::SKIP_GETTERS {
+            getters = true;
+        } else if ident == config::SKIP_SETTERS {
+            setters = true;
+        } else {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "expected `#[skip]`, `#[skip(getters)]` or `#[skip(setters)]`",
+            ));
+        }
+    }
+    Ok((getters, setters))
+}
+
+impl BitfieldConfig<'_> {
+    /// 第`index`个字段的起始位，即它之前所有字段位数之和，显式布局时为范围的起始位
+    pub fn field_offset(&self, index: usize) -> TokenStream2 {
+        if let Some(ref range) = self.fields[index].range {
+            let start = range.start;
+            return quote!(#start);
+        }
+        let bits = self.fields[..index].iter().map(FieldConfig::bits);
+        quote! {
+            (0 #(+ #bits)*)
+        }
+    }
+
+    /// 存储的位数，即所有字段的位数之和，`bytes = N`或`repr = uN`时为存储的大小
+    pub fn total_bits(&self) -> TokenStream2 {
+        if let Some(ref repr) = self.args.repr {
+            return quote! {
+                (#repr::BITS as usize)
+            };
+        }
+        if let Some(ref bytes) = self.args.bytes {
+            return quote! {
+                (#bytes * 8)
+            };
+        }
+        self.fields_bits()
+    }
+
+    /// 所有字段的位数之和，显式布局时为各范围结束位的最大值
+    pub fn fields_bits(&self) -> TokenStream2 {
+        let ends = self.fields.iter().filter_map(|field| field.range.as_ref());
+        if let Some(end) = ends.map(|range| range.end).max() {
+            return quote!(#end);
+        }
+        let bits = self.fields.iter().map(FieldConfig::bits);
+        quote! {
+            (0 #(+ #bits)*)
+        }
+    }
+
+    /// 存储所占的字节数
+    pub fn total_bytes(&self) -> TokenStream2 {
+        let total_bits = self.total_bits();
+        quote! {
+            (#total_bits / 8)
+        }
+    }
+
+    /// 第`index`个字段最低位在存储中的位置
+    ///
+    /// 把整个存储看作一个`total_bits`位的整数，`lsb0`时字段从该整数的最低位开始依次排列，
+    /// `msb0`时从最高位开始依次排列
+    pub fn field_position(&self, index: usize) -> TokenStream2 {
+        let offset = self.field_offset(index);
+        match self.args.bit_order {
+            BitOrder::Lsb0 => offset,
+            BitOrder::Msb0 => {
+                let total_bits = self.total_bits();
+                let bits = self.fields[index].bits();
+                quote! {
+                    (#total_bits - #offset - #bits)
+                }
+            }
+        }
+    }
+
+    /// 数组字段第`index`个元素最低位在存储中的位置，`index`为生成代码中的变量
+    ///
+    /// `msb0`时第0个元素位于字段的最高位
+    pub fn element_position(&self, index: usize) -> TokenStream2 {
+        let offset = self.field_offset(index);
+        let bits = self.fields[index].element_bits();
+        match self.args.bit_order {
+            BitOrder::Lsb0 => quote! {
+                (#offset + index * #bits)
+            },
+            BitOrder::Msb0 => {
+                let total_bits = self.total_bits();
+                quote! {
+                    (#total_bits - #offset - (index + 1) * #bits)
+                }
+            }
+        }
+    }
+
+    /// `#[bitfield(atomic)]`存储所用的原子整数类型
+    pub fn atomic_type(&self) -> TokenStream2 {
+        let total_bits = self.total_bits();
+        quote! {
+            <::bitfield::__private::BitCount<{ #total_bits }> as ::bitfield::__private::AtomicStorage>::Atomic
+        }
+    }
+
+    /// 读取`receiver`存储中第`position`位开始的`bits`位，得到`u64`
+    pub fn read_storage(
+        &self,
+        receiver: TokenStream2,
+        position: TokenStream2,
+        bits: TokenStream2,
+    ) -> TokenStream2 {
+        if self.args.repr.is_some() {
+            return quote! {
+                ::bitfield::__private::read_int(#receiver.data as u64, #position, #bits)
+            };
+        }
+        let big_endian = self.big_endian();
+        quote! {
+            ::bitfield::__private::read(&#receiver.data, #position, #bits, #big_endian)
+        }
+    }
+
+    /// 将`u64`类型的`raw`写入`receiver`存储中第`position`位开始的`bits`位
+    pub fn write_storage(
+        &self,
+        receiver: TokenStream2,
+        position: TokenStream2,
+        bits: TokenStream2,
+        raw: TokenStream2,
+    ) -> TokenStream2 {
+        if let Some(ref repr) = self.args.repr {
+            return quote! {
+                #receiver.data =
+                    ::bitfield::__private::write_int(#receiver.data as u64, #position, #bits, #raw) as #repr
+            };
+        }
+        let big_endian = self.big_endian();
+        quote! {
+            ::bitfield::__private::write(&mut #receiver.data, #position, #bits, #big_endian, #raw)
+        }
+    }
+
+    /// `receiver`存储的内容，类型为`[u8; N]`
+    pub fn storage_bytes(&self, receiver: TokenStream2) -> TokenStream2 {
+        if self.args.repr.is_none() {
+            return quote!(#receiver.data);
+        }
+        if self.big_endian() {
+            quote!(#receiver.data.to_be_bytes())
+        } else {
+            quote!(#receiver.data.to_le_bytes())
+        }
+    }
+
+    /// 是否为元组结构体
+    pub fn is_tuple(&self) -> bool {
+        matches!(
+            self.fields.first(),
+            Some(FieldConfig {
+                ident: FieldName::Unnamed(_),
+                ..
+            })
+        )
+    }
+
+    /// 存储是否为大端序
+    pub fn big_endian(&self) -> bool {
+        self.args.endian == Endian::Big
+    }
+}
+
+impl FieldConfig<'_> {
+    /// 是否为名称只由下划线组成的保留字段
+    pub fn is_reserved(&self) -> bool {
+        is_reserved(&self.ident)
+    }
+
+    /// 是否生成getter
+    pub fn has_getter(&self) -> bool {
+        !self.skip_getters
+    }
+
+    /// 是否生成setter
+    pub fn has_setter(&self) -> bool {
+        !self.skip_setters
+    }
+
+    /// 字段所占位数，数组字段为所有元素位数之和
+    pub fn bits(&self) -> TokenStream2 {
+        let bits = self.element_bits();
+        match self.len {
+            Some(len) => quote! {
+                (#bits * (#len))
+            },
+            None => bits,
+        }
+    }
+
+    /// 单个元素所占位数，非数组字段即字段所占位数
+    pub fn element_bits(&self) -> TokenStream2 {
+        let ty = self.ty;
+        quote! {
+            <#ty as ::bitfield::Specifier>::BITS
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! `#[derive(BitfieldSpecifier)]`的实现，让无字段枚举可以作为位域成员
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+
+use crate::{config, utils};
+
+/// 所拿到的枚举配置
+pub(crate) struct SpecifierConfig<'a> {
+    /// 枚举名称
+    name: &'a syn::Ident,
+    /// 枚举的各个变体，均为无字段变体
+    variants: Vec<&'a syn::Ident>,
+    /// 枚举所占位数
+    bits: usize,
+    /// 变体没有覆盖全部`2^bits`种位组合，getter需要返回`Result`
+    sparse: bool,
+    /// `#[specifier(serde)]`，生成以变体名表示的serde实现
+    serde: bool,
+}
+
+impl<'a> TryFrom<&'a syn::DeriveInput> for SpecifierConfig<'a> {
+    type Error = syn::Error;
+
+    fn try_from(input: &'a syn::DeriveInput) -> Result<Self, Self::Error> {
+        let data = match input.data {
+            syn::Data::Enum(ref data) => data,
+            _ => return Err(syn::Error::new_spanned(
+                &input.ident,
+                "BitfieldSpecifier can only be derived for enums, or for structs below #[bitfield]",
+            )),
+        };
+        let mut variants = Vec::with_capacity(data.variants.len());
+        for variant in &data.variants {
+            if !matches!(variant.fields, syn::Fields::Unit) {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "BitfieldSpecifier expected a variant without fields",
+                ));
+            }
+            variants.push(&variant.ident);
+        }
+        let declared_bits = input
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident(config::BITS_ATTR))
+            .map(utils::parse_bits_attr)
+            .transpose()?;
+        let mut serde = false;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(config::SPECIFIER_ATTR))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident(config::SERDE_ARG) {
+                    serde = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown #[specifier] argument"))
+                }
+            })?;
+            if serde && !cfg!(feature = "serde") {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "#[specifier(serde)] requires the `serde` feature of bitfield",
+                ));
+            }
+        }
+        let bits = match declared_bits {
+            Some(lit) => {
+                let bits = lit.base10_parse::<usize>()?;
+                if bits == 0 || bits > 64 {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "#[bits = N] expected N in the range 1..=64",
+                    ));
+                }
+                if variants.len() as u128 > 1u128 << bits {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        format!("{} variants do not fit in {} bits", variants.len(), bits),
+                    ));
+                }
+                bits
+            }
+            None if variants.len().is_power_of_two() => variants.len().trailing_zeros() as usize,
+            None => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "BitfieldSpecifier expected a number of variants which is a power of 2",
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: &input.ident,
+            sparse: (variants.len() as u128) < 1u128 << bits,
+            bits,
+            variants,
+            serde,
+        })
+    }
+}
+
+impl SpecifierConfig<'_> {
+    /// 生成`Specifier`实现以及每个判别值的范围检查，稀疏枚举的`Output`为`Result<Self, Unrecognized>`
+    /// ```ignore
+    /// impl ::bitfield::Specifier for TriggerMode {
+    ///     const BITS: usize = 1;
+    ///     type Bytes = u8;
+    ///     type InOut = TriggerMode;
+    ///     type Output = TriggerMode;
+    ///     fn into_bytes(input: TriggerMode) -> u8 {
+    ///         input as u8
+    ///     }
+    ///     fn from_bytes(bytes: u8) -> TriggerMode {
+    ///         match bytes {
+    ///             Discriminant::Edge => TriggerMode::Edge,
+    ///             Discriminant::Level => TriggerMode::Level,
+    ///             _ => unreachable!(),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 另外生成供`#[const_with]`字段在`const fn`中转换的`__bitfield_into_raw`
+    /// ```ignore
+    /// impl TriggerMode {
+    ///     pub const fn __bitfield_into_raw(input: TriggerMode) -> u64 {
+    ///         input as u64
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_specifier(&self) -> syn::Result<TokenStream2> {
+        let name = self.name;
+        let bits = self.bits;
+        let bytes = utils::bytes_type(bits);
+        let variants = &self.variants;
+        let checks = variants.iter().map(|variant| {
+            quote_spanned! {variant.span()=>
+                const _: () = ::bitfield::checks::discriminant_in_range::<
+                    <[(); ((#name::#variant as u128) < (1u128 << #bits)) as usize]
+                        as ::bitfield::checks::Bool>::Marker,
+                >();
+            }
+        });
+        let (output, from_bytes) = if self.sparse {
+            (
+                quote!(core::result::Result<#name, ::bitfield::Unrecognized>),
+                quote! {
+                    match bytes {
+                        #(Discriminant::#variants => core::result::Result::Ok(#name::#variants),)*
+                        _ => core::result::Result::Err(::bitfield::Unrecognized::new(bytes as u64)),
+                    }
+                },
+            )
+        } else {
+            (
+                quote!(#name),
+                quote! {
+                    match bytes {
+                        #(Discriminant::#variants => #name::#variants,)*
+                        _ => unreachable!("every {}-bit pattern names a variant", #bits),
+                    }
+                },
+            )
+        };
+        let serde = if self.serde {
+            self.generate_serde()
+        } else {
+            TokenStream2::new()
+        };
+        Ok(quote! {
+            #(#checks)*
+            #serde
+
+            impl ::bitfield::Specifier for #name {
+                const BITS: usize = #bits;
+                type Bytes = #bytes;
+                type InOut = #name;
+                type Output = #output;
+
+                #[inline]
+                fn into_bytes(input: #name) -> #bytes {
+                    input as #bytes
+                }
+
+                #[inline]
+                fn from_bytes(bytes: #bytes) -> #output {
+                    struct Discriminant;
+                    #[allow(non_upper_case_globals)]
+                    impl Discriminant {
+                        #(const #variants: #bytes = #name::#variants as #bytes;)*
+                    }
+                    #from_bytes
+                }
+            }
+
+            impl #name {
+                #[doc(hidden)]
+                #[inline]
+                pub const fn __bitfield_into_raw(input: #name) -> u64 {
+                    input as u64
+                }
+            }
+        })
+    }
+
+    /// 写有`#[specifier(serde)]`时生成以变体名序列化的`Serialize`与`Deserialize`实现
+    fn generate_serde(&self) -> TokenStream2 {
+        let name = self.name;
+        let name_literal = name.to_string();
+        let variants = &self.variants;
+        let variant_names = variants
+            .iter()
+            .map(|variant| variant.unraw().to_string())
+            .collect::<Vec<_>>();
+        let indexes = (0..variants.len() as u32).collect::<Vec<_>>();
+        let positions = 0..variants.len();
+        quote! {
+            impl ::bitfield::__private::serde::Serialize for #name {
+                fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+                where
+                    S: ::bitfield::__private::serde::Serializer,
+                {
+                    match self {
+                        #(#name::#variants => serializer.serialize_unit_variant(#name_literal, #indexes, #variant_names),)*
+                    }
+                }
+            }
+
+            impl<'de> ::bitfield::__private::serde::Deserialize<'de> for #name {
+                fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+                where
+                    D: ::bitfield::__private::serde::Deserializer<'de>,
+                {
+                    const VARIANTS: &[&str] = &[#(#variant_names),*];
+                    let index = ::bitfield::__private::serde::deserialize_variant(deserializer, #name_literal, VARIANTS)?;
+                    core::result::Result::Ok(match index {
+                        #(#positions => #name::#variants,)*
+                        _ => unreachable!(),
+                    })
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! 存放一些快捷判断逻辑
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+
+/// 能够容纳`bits`位的最窄无符号整数
+///
+/// # Arguments
+///
+/// * `bits`: 位数，不超过64
+///
+/// returns: `u8`、`u16`、`u32`或`u64`
+pub fn bytes_type(bits: usize) -> TokenStream2 {
+    match bits {
+        0..=8 => quote!(u8),
+        9..=16 => quote!(u16),
+        17..=32 => quote!(u32),
+        _ => quote!(u64),
+    }
+}
+
+/// 解析`#[bits = N]`标签中的`N`
+///
+/// # Arguments
+///
+/// * `attr`: 已确认路径为`bits`的标签
+///
+/// returns: `syn::Result<syn::LitInt>`
+pub fn parse_bits_attr(attr: &syn::Attribute) -> syn::Result<syn::LitInt> {
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(ref lit),
+                ..
+            }),
+        ..
+    }) = attr.meta
+    {
+        return Ok(lit.clone());
+    }
+    Err(syn::Error::new_spanned(attr, "expected `#[bits = N]`"))
+}
+
+/// 解析`#[bits(3..8)]`或`#[bits(3..=7)]`标签中的位范围，两端都必须是整数字面量
+///
+/// # Arguments
+///
+/// * `attr`: 已确认路径为`bits`的标签
+///
+/// returns: `syn::Result<(syn::ExprRange, usize, usize)>`，即范围本身与半开区间的起止位
+pub fn parse_bits_range_attr(attr: &syn::Attribute) -> syn::Result<(syn::ExprRange, usize, usize)> {
+    let error = || {
+        syn::Error::new_spanned(
+            attr,
+            "expected `#[bits(start..end)]` or `#[bits(start..=last)]`",
+        )
+    };
+    let range = attr.parse_args::<syn::ExprRange>().map_err(|_| error())?;
+    let bound = |expr: &Option<Box<syn::Expr>>| match expr.as_deref() {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        })) => lit.base10_parse::<usize>(),
+        _ => Err(error()),
+    };
+    let start = bound(&range.start)?;
+    let mut end = bound(&range.end)?;
+    if let syn::RangeLimits::Closed(_) = range.limits {
+        end += 1;
+    }
+    if start >= end {
+        return Err(syn::Error::new_spanned(
+            range,
+            "bit range must not be empty",
+        ));
+    }
+    Ok((range, start, end))
+}
+
+/// 不调用trait方法即可转换为原始位的内置类型
+pub enum Primitive {
+    /// `bool`，直接用`as u64`转换
+    Bool,
+    /// `B1`到`B64`，通过类型上的`__bitfield_into_raw`转换
+    Bits,
+}
+
+/// 判断是否是`bool`或`B1`到`B64`
+///
+/// 宏只能看到类型的写法，因此名为`B1`到`B64`的类型都被当作`bitfield`中的同名类型，
+/// 自定义的同名类型需要写出其他路径，例如`self::B8`
+///
+/// # Arguments
+///
+/// * `ty`: 字段类型 [`syn::Type`]
+///
+/// returns: `Option<Primitive>`
+pub fn primitive_specifier(ty: &syn::Type) -> Option<Primitive> {
+    let path = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path,
+        _ => return None,
+    };
+    let segment = path.segments.last().filter(|s| s.arguments.is_none())?;
+    // 只接受`B8`、`bool`或`bitfield::B8`这样的路径
+    let from_bitfield = match path.segments.len() {
+        1 => true,
+        2 => segment.ident != "bool" && path.segments[0].ident == "bitfield",
+        _ => false,
+    };
+    if !from_bitfield {
+        return None;
+    }
+    let name = segment.ident.to_string();
+    if name == "bool" {
+        return Some(Primitive::Bool);
+    }
+    let bits = name.strip_prefix('B')?;
+    let in_range = !bits.starts_with('0')
+        && bits
+            .parse::<usize>()
+            .is_ok_and(|bits| (1..=64).contains(&bits));
+    in_range.then_some(Primitive::Bits)
+}
+
+/// 解析`#[debug = "..."]`标签中的格式字符串
+///
+/// # Arguments
+///
+/// * `attr`: 已确认路径为`debug`的标签
+///
+/// returns: `syn::Result<syn::LitStr>`
+pub fn parse_debug_attr(attr: &syn::Attribute) -> syn::Result<syn::LitStr> {
+    if let syn::Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(ref lit),
+                ..
+            }),
+        ..
+    }) = attr.meta
+    {
+        return Ok(lit.clone());
+    }
+    Err(syn::Error::new_spanned(
+        attr,
+        "expected `#[debug = \"...\"]`",
+    ))
+}
+
+/// 从`#[derive(...)]`中移除名为`name`的derive，返回是否找到
+/// 移除后为空的`#[derive()]`会被整个删除
+///
+/// # Arguments
+///
+/// * `attrs`: 结构体上的属性
+/// * `name`: derive名称，只比较路径的最后一段，例如`BitfieldSpecifier`
+///
+/// returns: `syn::Result<bool>`
+pub fn take_derive(attrs: &mut Vec<syn::Attribute>, name: &str) -> syn::Result<bool> {
+    let mut found = false;
+    let mut ret = Vec::with_capacity(attrs.len());
+    for mut attr in attrs.drain(..) {
+        if attr.path().is_ident("derive") {
+            let paths =
+                attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)?;
+            let len = paths.len();
+            let paths = paths
+                .into_iter()
+                .filter(|path| path.segments.last().is_none_or(|s| s.ident != name))
+                .collect::<Punctuated<syn::Path, syn::Token![,]>>();
+            if paths.len() != len {
+                found = true;
+                if paths.is_empty() {
+                    continue;
+                }
+                attr.meta = syn::parse_quote!(derive(#paths));
+            }
+        }
+        ret.push(attr);
+    }
+    *attrs = ret;
+    Ok(found)
+}
@@ -0,0 +1,212 @@
+//! 供`#[bitfield]`生成代码调用的位读写函数，不属于公开API
+//!
+//! 整个存储被看作一个`data.len() * 8`位的整数，`pos`是该整数中的位序号，0为最低位。
+//! 小端序时第一个字节存放最低的8位，大端序时第一个字节存放最高的8位。
+
+#[cfg(target_has_atomic = "16")]
+use core::sync::atomic::AtomicU16;
+#[cfg(target_has_atomic = "32")]
+use core::sync::atomic::AtomicU32;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::Ordering;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+/// 整数中第`pos`位所在的字节
+const fn byte_index(len: usize, pos: usize, big_endian: bool) -> usize {
+    if big_endian {
+        len - 1 - pos / 8
+    } else {
+        pos / 8
+    }
+}
+
+/// 从第`pos`位开始读取`width`位，低位在前
+pub const fn read(data: &[u8], pos: usize, width: usize, big_endian: bool) -> u64 {
+    let mut value = 0u64;
+    let mut i = 0;
+    while i < width {
+        let bit_pos = pos + i;
+        let byte = data[byte_index(data.len(), bit_pos, big_endian)];
+        let bit = (byte >> (bit_pos % 8)) & 1;
+        value |= (bit as u64) << i;
+        i += 1;
+    }
+    value
+}
+
+/// 将`value`的低`width`位写入第`pos`位开始处，超出`width`的位会被丢弃
+pub const fn write(data: &mut [u8], pos: usize, width: usize, big_endian: bool, value: u64) {
+    let mut i = 0;
+    while i < width {
+        let bit_pos = pos + i;
+        let index = byte_index(data.len(), bit_pos, big_endian);
+        let mask = 1u8 << (bit_pos % 8);
+        if (value >> i) & 1 == 1 {
+            data[index] |= mask;
+        } else {
+            data[index] &= !mask;
+        }
+        i += 1;
+    }
+}
+
+/// 从整数存储的第`pos`位开始读取`width`位
+pub const fn read_int(data: u64, pos: usize, width: usize) -> u64 {
+    let value = data >> pos;
+    if width >= 64 {
+        value
+    } else {
+        value & ((1 << width) - 1)
+    }
+}
+
+/// 将`value`的低`width`位写入整数存储的第`pos`位开始处，返回写入后的存储
+pub const fn write_int(data: u64, pos: usize, width: usize, value: u64) -> u64 {
+    let mask = mask(pos, width);
+    (data & !mask) | ((value << pos) & mask)
+}
+
+/// `raw`是否能放进`width`位
+pub const fn fits(raw: u64, width: usize) -> bool {
+    width >= 64 || raw >> width == 0
+}
+
+/// 第`pos`位开始、共`width`位的掩码，超出64位时在编译期报错
+pub const fn mask(pos: usize, width: usize) -> u64 {
+    assert!(pos + width <= 64, "field mask does not fit in u64");
+    if width == 64 {
+        u64::MAX
+    } else {
+        ((1 << width) - 1) << pos
+    }
+}
+
+/// 将读取到的原始位转换为[`crate::Specifier::Bytes`]，读取的位数不会超过该类型的宽度
+#[inline]
+pub fn narrow<T: TryFrom<u64>>(raw: u64) -> T {
+    match T::try_from(raw) {
+        Ok(bytes) => bytes,
+        Err(_) => unreachable!("bitfield read more bits than Specifier::Bytes can hold"),
+    }
+}
+
+/// 以位数作为类型参数，供`Storage`按位数选择整数类型
+pub struct BitCount<const N: usize>;
+
+/// 能够容纳`N`位的最窄无符号整数
+pub trait Storage {
+    /// 对应的无符号整数
+    type Bytes;
+}
+
+macro_rules! storage {
+    ($($start:literal..$end:literal => $ty:ty,)*) => {
+        $(
+            seq::seq!(N in $start..$end {
+                impl Storage for BitCount<N> {
+                    type Bytes = $ty;
+                }
+            });
+        )*
+    };
+}
+
+storage! {
+    1..9 => u8,
+    9..17 => u16,
+    17..33 => u32,
+    33..65 => u64,
+}
+
+/// 能够容纳`N`位的最窄原子整数，供`#[bitfield(atomic)]`使用
+pub trait AtomicStorage {
+    /// 对应的原子整数
+    type Atomic: Atomic;
+}
+
+/// 以`u64`统一操作各宽度的原子整数
+pub trait Atomic {
+    /// 构造原子整数，`value`不会超出其宽度
+    fn new(value: u64) -> Self;
+    /// 读取当前值
+    fn load(&self, order: Ordering) -> u64;
+    /// 取出当前值
+    fn into_inner(self) -> u64;
+    /// 同标准库原子整数的`fetch_update`
+    fn fetch_update<F: FnMut(u64) -> Option<u64>>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        f: F,
+    ) -> Result<u64, u64>;
+}
+
+macro_rules! atomic_storage {
+    ($($(#[$attr:meta])* $start:literal..$end:literal => $atomic:ty, $ty:ty,)*) => {
+        $(
+            $(#[$attr])*
+            impl Atomic for $atomic {
+                #[inline]
+                fn new(value: u64) -> Self {
+                    <$atomic>::new(value as $ty)
+                }
+
+                #[inline]
+                fn load(&self, order: Ordering) -> u64 {
+                    <$atomic>::load(self, order) as u64
+                }
+
+                #[inline]
+                fn into_inner(self) -> u64 {
+                    <$atomic>::into_inner(self) as u64
+                }
+
+                #[inline]
+                fn fetch_update<F: FnMut(u64) -> Option<u64>>(
+                    &self,
+                    set_order: Ordering,
+                    fetch_order: Ordering,
+                    mut f: F,
+                ) -> Result<u64, u64> {
+                    <$atomic>::fetch_update(self, set_order, fetch_order, |value| {
+                        f(value as u64).map(|value| value as $ty)
+                    })
+                    .map(|value| value as u64)
+                    .map_err(|value| value as u64)
+                }
+            }
+
+            seq::seq!(N in $start..$end {
+                $(#[$attr])*
+                impl AtomicStorage for BitCount<N> {
+                    type Atomic = $atomic;
+                }
+            });
+        )*
+    };
+}
+
+atomic_storage! {
+    #[cfg(target_has_atomic = "8")]
+    1..9 => AtomicU8, u8,
+    #[cfg(target_has_atomic = "16")]
+    9..17 => AtomicU16, u16,
+    #[cfg(target_has_atomic = "32")]
+    17..33 => AtomicU32, u32,
+    #[cfg(target_has_atomic = "64")]
+    33..65 => AtomicU64, u64,
+}
+
+/// `fetch_update`以`order`写入时，比较失败后读取所用的顺序
+pub const fn failure_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        order => order,
+    }
+}
@@ -0,0 +1,331 @@
+//! 供`#[bitfield]`与`#[derive(BitfieldSpecifier)]`生成的serde实现调用
+
+use core::fmt;
+use core::marker::PhantomData;
+
+pub use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Unrecognized;
+
+/// 包装字段getter的返回值，通过[`PlainValue::repr`]或[`SparseValue::repr`]取得序列化方式
+///
+/// 生成的代码以`(&Value(&value)).repr()`调用，稀疏枚举的`Result<T, Unrecognized>`优先匹配
+/// [`SparseValue`]，无法识别的位序列化为原始整数；其他类型匹配[`PlainValue`]，按原样序列化
+pub struct Value<'a, T>(pub &'a T);
+
+/// 按原样序列化的字段值
+pub trait PlainValue<'a> {
+    /// 用于序列化的类型
+    type Repr: Serialize;
+    /// 取得用于序列化的值
+    fn repr(&self) -> Self::Repr;
+}
+
+impl<'a, T: Serialize> PlainValue<'a> for &Value<'a, T> {
+    type Repr = &'a T;
+
+    #[inline]
+    fn repr(&self) -> &'a T {
+        self.0
+    }
+}
+
+/// 稀疏枚举getter返回的`Result<T, Unrecognized>`
+pub trait SparseValue<'a> {
+    /// 用于序列化的类型
+    type Repr: Serialize;
+    /// 取得用于序列化的值
+    fn repr(&self) -> Self::Repr;
+}
+
+impl<'a, T: Serialize> SparseValue<'a> for Value<'a, Result<T, Unrecognized>> {
+    type Repr = Sparse<'a, T>;
+
+    #[inline]
+    fn repr(&self) -> Sparse<'a, T> {
+        Sparse(self.0)
+    }
+}
+
+/// 识别出的变体序列化为变体本身，无法识别的位序列化为原始整数
+pub struct Sparse<'a, T>(&'a Result<T, Unrecognized>);
+
+impl<T: Serialize> Serialize for Sparse<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Ok(value) => value.serialize(serializer),
+            Err(unrecognized) => serializer.serialize_u64(unrecognized.raw_value()),
+        }
+    }
+}
+
+/// 以字段类型的`Output`与`InOut`选择反序列化方式，通过[`PlainField::repr`]或[`SparseField::repr`]取得
+///
+/// 生成的代码以`(&FieldType::<Output, InOut>(PhantomData)).repr()`调用，`Output`为`Result<InOut, Unrecognized>`的
+/// 稀疏枚举优先匹配[`SparseField`]，除变体外还接受序列化时写出的原始整数；其他类型匹配[`PlainField`]
+pub struct FieldType<O, I>(pub PhantomData<(O, I)>);
+
+/// 按`InOut`反序列化的字段
+pub trait PlainField {
+    /// 用于反序列化的类型
+    type Repr;
+    /// 取得用于反序列化的类型
+    fn repr(&self) -> PhantomData<Self::Repr>;
+}
+
+impl<O, I> PlainField for &FieldType<O, I> {
+    type Repr = I;
+
+    #[inline]
+    fn repr(&self) -> PhantomData<I> {
+        PhantomData
+    }
+}
+
+/// getter返回`Result<T, Unrecognized>`的稀疏枚举字段
+pub trait SparseField {
+    /// 用于反序列化的类型
+    type Repr;
+    /// 取得用于反序列化的类型
+    fn repr(&self) -> PhantomData<Self::Repr>;
+}
+
+impl<T> SparseField for FieldType<Result<T, Unrecognized>, T> {
+    type Repr = Field<T>;
+
+    #[inline]
+    fn repr(&self) -> PhantomData<Field<T>> {
+        PhantomData
+    }
+}
+
+/// 反序列化得到的字段值
+pub enum Field<T> {
+    /// 交给`set_..._checked`写入的值
+    Value(T),
+    /// 稀疏枚举中无法识别的原始位，由[`Sparse`]序列化为整数
+    Raw(u64),
+}
+
+impl<T> From<T> for Field<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Field::Value(value)
+    }
+}
+
+/// 接受变体或原始整数，与[`Sparse`]的序列化结果对应
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Field<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+            type Value = Field<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a variant name or raw bits")
+            }
+
+            fn visit_u64<E: de::Error>(self, raw: u64) -> Result<Self::Value, E> {
+                Ok(Field::Raw(raw))
+            }
+
+            fn visit_i64<E: de::Error>(self, raw: i64) -> Result<Self::Value, E> {
+                match u64::try_from(raw) {
+                    Ok(raw) => Ok(Field::Raw(raw)),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Signed(raw), &self)),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                T::deserialize(de::IntoDeserializer::<E>::into_deserializer(name)).map(Field::Value)
+            }
+
+            fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                T::deserialize(de::value::EnumAccessDeserializer::new(data)).map(Field::Value)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+/// 以[`PlainField::repr`]或[`SparseField::repr`]选出的类型读取一个字段的值
+pub fn next_field<'de, A, R, I>(map: &mut A, _: PhantomData<R>) -> Result<Field<I>, A::Error>
+where
+    A: de::MapAccess<'de>,
+    R: Deserialize<'de> + Into<Field<I>>,
+{
+    map.next_value::<R>().map(Into::into)
+}
+
+/// 同[`next_field`]，用于数组字段
+pub fn next_array<'de, A, R, I, const N: usize>(
+    map: &mut A,
+    _: PhantomData<R>,
+) -> Result<[Field<I>; N], A::Error>
+where
+    A: de::MapAccess<'de>,
+    R: Deserialize<'de> + Into<Field<I>>,
+{
+    map.next_value::<Array<R, N>>()
+        .map(|values| values.0.map(Into::into))
+}
+
+/// 把迭代器序列化为序列，用于数组字段
+pub struct Seq<I>(pub I);
+
+impl<I> Serialize for Seq<I>
+where
+    I: Iterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.clone())
+    }
+}
+
+/// 反序列化数组字段，长度不受serde内置数组实现的32个元素限制
+pub struct Array<T, const N: usize>(pub [T; N]);
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for Array<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> de::Visitor<'de> for Visitor<T, N> {
+            type Value = Array<T, N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an array of length {}", N)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut values = [(); N].map(|_| None);
+                for (index, value) in values.iter_mut().enumerate() {
+                    match seq.next_element()? {
+                        Some(element) => *value = Some(element),
+                        None => return Err(de::Error::invalid_length(index, &self)),
+                    }
+                }
+                Ok(Array(values.map(Option::unwrap)))
+            }
+        }
+
+        deserializer.deserialize_tuple(N, Visitor(PhantomData))
+    }
+}
+
+/// 非人类可读格式中的存储，接受字节串或由字节组成的序列
+pub fn deserialize_bytes<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    struct Visitor<const N: usize>;
+
+    impl<'de, const N: usize> de::Visitor<'de> for Visitor<N> {
+        type Value = [u8; N];
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} bytes", N)
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+            bytes
+                .try_into()
+                .map_err(|_| de::Error::invalid_length(bytes.len(), &self))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = [0; N];
+            for (index, byte) in bytes.iter_mut().enumerate() {
+                match seq.next_element()? {
+                    Some(value) => *byte = value,
+                    None => return Err(de::Error::invalid_length(index, &self)),
+                }
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_bytes(Visitor::<N>)
+}
+
+/// 把结构体字段名或枚举变体名反序列化为它在`names`中的序号
+pub struct Identifier {
+    /// 所有合法的名称
+    pub names: &'static [&'static str],
+    /// `true`时为枚举变体，否则为结构体字段
+    pub variant: bool,
+}
+
+impl<'de> de::DeserializeSeed<'de> for Identifier {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl de::Visitor<'_> for Identifier {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.variant {
+            "a variant name"
+        } else {
+            "a field name"
+        })
+    }
+
+    fn visit_u64<E: de::Error>(self, index: u64) -> Result<usize, E> {
+        match usize::try_from(index) {
+            Ok(index) if index < self.names.len() => Ok(index),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(index),
+                &self,
+            )),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<usize, E> {
+        match self.names.iter().position(|candidate| *candidate == name) {
+            Some(index) => Ok(index),
+            None if self.variant => Err(de::Error::unknown_variant(name, self.names)),
+            None => Err(de::Error::unknown_field(name, self.names)),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, name: &[u8]) -> Result<usize, E> {
+        match core::str::from_utf8(name) {
+            Ok(name) => self.visit_str(name),
+            Err(_) => Err(de::Error::invalid_value(de::Unexpected::Bytes(name), &self)),
+        }
+    }
+}
+
+/// 反序列化无字段枚举，返回变体在`variants`中的序号
+pub fn deserialize_variant<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    name: &'static str,
+    variants: &'static [&'static str],
+) -> Result<usize, D::Error> {
+    struct Visitor(&'static [&'static str]);
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a unit variant")
+        }
+
+        fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<usize, A::Error> {
+            let (index, variant) = data.variant_seed(Identifier {
+                names: self.0,
+                variant: true,
+            })?;
+            de::VariantAccess::unit_variant(variant)?;
+            Ok(index)
+        }
+    }
+
+    deserializer.deserialize_enum(name, variants, Visitor(variants))
+}
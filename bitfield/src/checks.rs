@@ -0,0 +1,67 @@
+//! `#[bitfield]`生成代码时所使用的编译期检查
+
+/// 总位数是8的倍数时才会实现此trait
+pub trait TotalSizeIsMultipleOfEightBits {}
+
+/// 将`[(); BITS % 8]`映射为对应的余数类型
+pub trait Mod8 {
+    /// 余数所对应的类型
+    type Marker;
+}
+
+macro_rules! mod8 {
+    ($($n:literal => $marker:ident,)*) => {
+        $(
+            #[doc(hidden)]
+            pub enum $marker {}
+
+            impl Mod8 for [(); $n] {
+                type Marker = $marker;
+            }
+        )*
+    };
+}
+
+mod8! {
+    0 => ZeroMod8,
+    1 => OneMod8,
+    2 => TwoMod8,
+    3 => ThreeMod8,
+    4 => FourMod8,
+    5 => FiveMod8,
+    6 => SixMod8,
+    7 => SevenMod8,
+}
+
+impl TotalSizeIsMultipleOfEightBits for ZeroMod8 {}
+
+/// 只有`T`满足[`TotalSizeIsMultipleOfEightBits`]时才能通过编译
+pub const fn total_size_is_multiple_of_eight_bits<T: TotalSizeIsMultipleOfEightBits>() {}
+
+/// 判别值在`0..2^BITS`范围内时才会实现此trait
+pub trait DiscriminantInRange {}
+
+/// 将`[(); 0]`与`[(); 1]`映射为[`False`]与[`True`]
+pub trait Bool {
+    /// 布尔值所对应的类型
+    type Marker;
+}
+
+#[doc(hidden)]
+pub enum False {}
+
+#[doc(hidden)]
+pub enum True {}
+
+impl Bool for [(); 0] {
+    type Marker = False;
+}
+
+impl Bool for [(); 1] {
+    type Marker = True;
+}
+
+impl DiscriminantInRange for True {}
+
+/// 只有`T`满足[`DiscriminantInRange`]时才能通过编译
+pub const fn discriminant_in_range<T: DiscriminantInRange>() {}
@@ -0,0 +1,70 @@
+//! 位域访问时可能出现的错误
+
+use core::fmt;
+
+/// 稀疏枚举的getter读取到了不对应任何变体的位
+///
+/// 只有带`#[bits = N]`且变体数量少于`2^N`的`BitfieldSpecifier`枚举会返回此错误，
+/// 原始位可以通过[`Unrecognized::raw_value`]取得
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unrecognized {
+    raw: u64,
+}
+
+impl Unrecognized {
+    /// 由原始位构造错误
+    pub const fn new(raw: u64) -> Self {
+        Self { raw }
+    }
+
+    /// 读取到的原始位
+    pub const fn raw_value(&self) -> u64 {
+        self.raw
+    }
+}
+
+impl fmt::Display for Unrecognized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unrecognized bit pattern {:#b}", self.raw)
+    }
+}
+
+impl std::error::Error for Unrecognized {}
+
+/// setter收到的值超出了字段的位数
+///
+/// 由`set_..._checked`返回，此时字段保持原值不变
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutOfBounds {
+    raw: u64,
+    bits: usize,
+}
+
+impl OutOfBounds {
+    /// 由越界的原始位与字段位数构造错误
+    pub const fn new(raw: u64, bits: usize) -> Self {
+        Self { raw, bits }
+    }
+
+    /// 越界的原始位
+    pub const fn raw_value(&self) -> u64 {
+        self.raw
+    }
+
+    /// 字段所占的位数
+    pub const fn bits(&self) -> usize {
+        self.bits
+    }
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value {:#x} does not fit in {} bits",
+            self.raw, self.bits
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
@@ -10,6 +10,192 @@
 //
 // From the perspective of a user of this crate, they get all the necessary APIs
 // (macro, trait, struct) through the one bitfield crate.
-pub use bitfield_impl::bitfield;
+pub use bitfield_impl::{bitfield, BitfieldSpecifier};
+pub use error::{OutOfBounds, Unrecognized};
+pub use register::Register;
 
-// TODO other things
+#[doc(hidden)]
+pub mod __private;
+pub mod checks;
+mod error;
+mod register;
+
+/// 位域成员的类型说明
+///
+/// `#[bitfield]`结构体的每个字段类型都必须实现此trait，setter接收[`Specifier::InOut`]，
+/// getter返回[`Specifier::Output`]。除了内置的`B1`到`B64`、有符号的`I1`到`I64`与`bool`，也可以为自定义类型实现它：
+/// ```
+/// use bitfield::Specifier;
+///
+/// pub struct Version {
+///     major: u8,
+///     minor: u8,
+/// }
+///
+/// impl Specifier for Version {
+///     const BITS: usize = 8;
+///     type Bytes = u8;
+///     type InOut = Version;
+///     type Output = Version;
+///
+///     fn into_bytes(input: Version) -> u8 {
+///         input.major << 4 | input.minor & 0x0f
+///     }
+///
+///     fn from_bytes(bytes: u8) -> Version {
+///         Version { major: bytes >> 4, minor: bytes & 0x0f }
+///     }
+/// }
+/// ```
+pub trait Specifier {
+    /// 成员所占的位数
+    const BITS: usize;
+    /// 能够容纳`BITS`位的最窄无符号整数
+    type Bytes: Copy + Into<u64> + TryFrom<u64>;
+    /// setter接收的类型
+    type InOut;
+    /// getter返回的类型，通常与`InOut`相同，无法识别所有位组合的类型可以返回`Result`
+    type Output;
+
+    /// 将setter接收的值转换为原始位
+    fn into_bytes(input: Self::InOut) -> Self::Bytes;
+
+    /// 将读取到的原始位转换为getter返回的值
+    fn from_bytes(bytes: Self::Bytes) -> Self::Output;
+
+    /// setter写入前检查[`Specifier::into_bytes`]得到的原始位是否有效，默认要求能放进`BITS`位
+    ///
+    /// 有符号类型的`into_bytes`得到的是整个`Bytes`宽度的补码，需要改为检查取值范围
+    #[inline]
+    fn fits(raw: u64) -> bool {
+        __private::fits(raw, Self::BITS)
+    }
+}
+
+/// 由`#[bitfield]`为结构体实现，表示它只由字节数组或无符号整数存储构成
+///
+/// `from_ne_bytes`与`into_ne_bytes`使用存储在内存中的字节，对`repr = uN`的结构体即整数的本机字节序，
+/// 与`endian`参数无关；按`endian`转换的是结构体自身的`from_bytes`与`into_bytes`
+///
+/// # Safety
+///
+/// 实现者必须与[`Bitfield::Bytes`]大小相同，并且`Bytes`的任意取值都合法，
+/// [`Register`]依赖这一点以整数宽度读写寄存器
+pub unsafe trait Bitfield: Sized {
+    /// 存储所用的字节数组`[u8; N]`
+    type Bytes: Copy;
+
+    /// 以内存中的字节构造
+    fn from_ne_bytes(bytes: Self::Bytes) -> Self;
+
+    /// 取出内存中的字节
+    fn into_ne_bytes(self) -> Self::Bytes;
+}
+
+impl Specifier for bool {
+    const BITS: usize = 1;
+    type Bytes = u8;
+    type InOut = bool;
+    type Output = bool;
+
+    #[inline]
+    fn into_bytes(input: bool) -> u8 {
+        input as u8
+    }
+
+    #[inline]
+    fn from_bytes(bytes: u8) -> bool {
+        bytes != 0
+    }
+}
+
+/// 为`B1`到`B64`生成类型，`$ty`为这一组类型所使用的最窄无符号整数
+macro_rules! specifiers {
+    ($($start:literal..$end:literal => $ty:ty,)*) => {
+        $(
+            seq::seq!(N in $start..$end {
+                /// 占用固定位数的位域成员类型
+                pub enum B~N {}
+
+                impl Specifier for B~N {
+                    const BITS: usize = N;
+                    type Bytes = $ty;
+                    type InOut = $ty;
+                    type Output = $ty;
+
+                    #[inline]
+                    fn into_bytes(input: $ty) -> $ty {
+                        input
+                    }
+
+                    #[inline]
+                    fn from_bytes(bytes: $ty) -> $ty {
+                        bytes
+                    }
+                }
+
+                impl B~N {
+                    /// 供`#[bitfield]`生成的`const fn with_`转换，不属于公开API
+                    #[doc(hidden)]
+                    #[inline]
+                    pub const fn __bitfield_into_raw(input: $ty) -> u64 {
+                        input as u64
+                    }
+                }
+            });
+        )*
+    };
+}
+
+specifiers! {
+    1..9 => u8,
+    9..17 => u16,
+    17..33 => u32,
+    33..65 => u64,
+}
+
+/// 为`I1`到`I64`生成类型，`$ty`为存储原始位的无符号整数，`$signed`为同宽度的有符号整数
+macro_rules! signed_specifiers {
+    ($($start:literal..$end:literal => $ty:ty, $signed:ty,)*) => {
+        $(
+            seq::seq!(N in $start..$end {
+                /// 占用固定位数、以补码表示的有符号位域成员类型
+                ///
+                /// getter对读取到的位做符号扩展，setter只接受`-2^(N-1)..2^(N-1)`范围内的值
+                pub enum I~N {}
+
+                impl Specifier for I~N {
+                    const BITS: usize = N;
+                    type Bytes = $ty;
+                    type InOut = $signed;
+                    type Output = $signed;
+
+                    #[inline]
+                    fn into_bytes(input: $signed) -> $ty {
+                        input as $ty
+                    }
+
+                    #[inline]
+                    fn from_bytes(bytes: $ty) -> $signed {
+                        let shift = <$signed>::BITS - N;
+                        (bytes as $signed) << shift >> shift
+                    }
+
+                    #[inline]
+                    fn fits(raw: u64) -> bool {
+                        let shift = <$signed>::BITS - N;
+                        let value = raw as $ty as $signed;
+                        value << shift >> shift == value
+                    }
+                }
+            });
+        )*
+    };
+}
+
+signed_specifiers! {
+    1..9 => u8, i8,
+    9..17 => u16, i16,
+    17..33 => u32, i32,
+    33..65 => u64, i64,
+}
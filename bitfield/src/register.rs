@@ -0,0 +1,89 @@
+//! 映射到设备内存的位域寄存器
+
+use core::marker::PhantomData;
+use core::mem::{size_of, transmute_copy};
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::Bitfield;
+
+/// 指向设备内存中一个`#[bitfield]`寄存器的指针，所有访问都是volatile的
+///
+/// 存储为1、2、4或8字节时以同样宽度的整数一次读写整个寄存器，其他大小时整体volatile读写字节数组
+/// ```
+/// use bitfield::*;
+///
+/// #[bitfield]
+/// pub struct Control {
+///     enable: bool,
+///     mode: B7,
+/// }
+///
+/// let mut memory = 0u8;
+/// let control = unsafe { Register::new(&mut memory as *mut u8 as *mut Control) };
+/// control.modify(|r| r.set_enable(true));
+/// assert!(control.read().get_enable());
+/// assert_eq!(memory, 1);
+/// ```
+pub struct Register<T> {
+    ptr: *mut T,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Bitfield> Register<T> {
+    /// 由寄存器地址构造
+    ///
+    /// # Safety
+    ///
+    /// `ptr`在`Register`的整个生命周期内必须可以读写`size_of::<T>()`个字节，
+    /// 并且按存储大小对应的整数类型对齐
+    pub const unsafe fn new(ptr: *mut T) -> Self {
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 寄存器地址
+    pub const fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    /// 读取整个寄存器
+    pub fn read(&self) -> T {
+        let ptr = self.ptr.cast::<T::Bytes>();
+        // SAFETY: `new`保证了地址可读且已对齐，`Bitfield`保证了`T::Bytes`与`T`大小相同且任意字节都合法
+        let bytes = unsafe {
+            match size_of::<T::Bytes>() {
+                1 => transmute_copy(&read_volatile(ptr.cast::<u8>())),
+                2 => transmute_copy(&read_volatile(ptr.cast::<u16>())),
+                4 => transmute_copy(&read_volatile(ptr.cast::<u32>())),
+                8 => transmute_copy(&read_volatile(ptr.cast::<u64>())),
+                _ => read_volatile(ptr),
+            }
+        };
+        T::from_ne_bytes(bytes)
+    }
+
+    /// 写入整个寄存器
+    pub fn write(&self, value: T) {
+        let ptr = self.ptr.cast::<T::Bytes>();
+        let bytes = value.into_ne_bytes();
+        // SAFETY: 同`read`
+        unsafe {
+            match size_of::<T::Bytes>() {
+                1 => write_volatile(ptr.cast::<u8>(), transmute_copy(&bytes)),
+                2 => write_volatile(ptr.cast::<u16>(), transmute_copy(&bytes)),
+                4 => write_volatile(ptr.cast::<u32>(), transmute_copy(&bytes)),
+                8 => write_volatile(ptr.cast::<u64>(), transmute_copy(&bytes)),
+                _ => write_volatile(ptr, bytes),
+            }
+        }
+    }
+
+    /// 读取寄存器，交给`f`修改后写回
+    pub fn modify<F: FnOnce(&mut T)>(&self, f: F) {
+        let mut value = self.read();
+        f(&mut value);
+        self.write(value);
+    }
+}
@@ -0,0 +1,56 @@
+// The Specifier trait is not reserved for the B* types. Any type can be used as
+// a bitfield member by implementing it by hand: BITS gives the width, Bytes is
+// the narrowest unsigned integer holding that many bits, InOut is the type
+// taken by the setter and Output is the type returned by the getter. A
+// #[bits = N] attribute on such a field is checked against BITS just like for
+// the built-in types.
+
+use bitfield::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Version {
+    major: u8,
+    minor: u8,
+}
+
+impl Specifier for Version {
+    const BITS: usize = 8;
+    type Bytes = u8;
+    type InOut = Version;
+    type Output = Version;
+
+    fn into_bytes(input: Version) -> u8 {
+        input.major << 4 | input.minor & 0x0f
+    }
+
+    fn from_bytes(bytes: u8) -> Version {
+        Version {
+            major: bytes >> 4,
+            minor: bytes & 0x0f,
+        }
+    }
+}
+
+#[bitfield]
+pub struct Header {
+    #[bits = 8]
+    version: Version,
+    compressed: bool,
+    length: B15,
+}
+
+fn main() {
+    assert_eq!(<Version as Specifier>::BITS, 8);
+    assert_eq!(std::mem::size_of::<Header>(), 3);
+
+    let mut header = Header::new();
+    assert_eq!(header.get_version(), Version { major: 0, minor: 0 });
+    assert_eq!(header.get_compressed(), false);
+
+    header.set_version(Version { major: 1, minor: 3 });
+    header.set_compressed(true);
+    header.set_length(0x7fff);
+    assert_eq!(header.get_version(), Version { major: 1, minor: 3 });
+    assert_eq!(header.get_compressed(), true);
+    assert_eq!(header.get_length(), 0x7fff);
+}
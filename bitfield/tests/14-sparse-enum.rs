@@ -0,0 +1,48 @@
+// An enum whose variants do not cover every bit pattern can still be used as a
+// bitfield member when its width is given explicitly with #[bits = N]. Reading
+// such a field may find a pattern that names no variant, so its getter returns
+// Result<T, Unrecognized> with the raw bits available through the error, while
+// the setter keeps taking the enum itself.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Register {
+    small_prime: SmallPrime,
+    #[bits = 4]
+    parity: Parity,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+#[bits = 4]
+pub enum SmallPrime {
+    Two = 0b0010,
+    Three = 0b0011,
+    Five = 0b0101,
+    Seven = 0b0111,
+    Eleven = 0b1011,
+    Thirteen = 0b1101,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+#[bits = 4]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+fn main() {
+    assert_eq!(<SmallPrime as Specifier>::BITS, 4);
+    assert_eq!(std::mem::size_of::<Register>(), 1);
+
+    let mut register = Register::new();
+    assert_eq!(0, register.get_small_prime().unwrap_err().raw_value());
+    assert_eq!(register.get_parity(), Ok(Parity::None));
+
+    register.set_small_prime(SmallPrime::Seven);
+    register.set_parity(Parity::Odd);
+    let p = register.get_small_prime().unwrap_or(SmallPrime::Two);
+    assert_eq!(p, SmallPrime::Seven);
+    assert_eq!(register.get_parity(), Ok(Parity::Odd));
+}
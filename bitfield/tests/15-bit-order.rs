@@ -0,0 +1,64 @@
+// The storage of a bitfield can be read as one integer spanning all its bytes.
+// By default the first field occupies the least significant bits of that
+// integer and the first byte holds its least significant 8 bits. Network
+// headers and device registers often want the opposite, which is selected with
+// #[bitfield(bit_order = "msb0")] and #[bitfield(endian = "big")].
+
+use bitfield::*;
+
+#[bitfield(bit_order = "msb0", endian = "big")]
+pub struct Ipv4Prefix {
+    version: B4,
+    ihl: B4,
+    dscp: B6,
+    ecn: B2,
+    total_length: B16,
+}
+
+#[bitfield]
+pub struct Lsb0Little {
+    a: B4,
+    b: B12,
+}
+
+#[bitfield(bit_order = "lsb0", endian = "big")]
+pub struct Lsb0Big {
+    a: B4,
+    b: B12,
+}
+
+#[bitfield(bit_order = "msb0")]
+pub struct Msb0Little {
+    a: B4,
+    b: B12,
+}
+
+fn main() {
+    let mut header = Ipv4Prefix::new();
+    header.set_version(4);
+    header.set_ihl(5);
+    header.set_ecn(0b11);
+    header.set_total_length(0x0054);
+    assert_eq!(header.into_bytes(), [0x45, 0b0000_0011, 0x00, 0x54]);
+
+    let mut x = Lsb0Little::new();
+    x.set_a(0xA);
+    x.set_b(0xBCD);
+    assert_eq!(x.get_a(), 0xA);
+    assert_eq!(x.get_b(), 0xBCD);
+    assert_eq!(x.into_bytes(), [0xDA, 0xBC]);
+
+    let mut x = Lsb0Big::new();
+    x.set_a(0xA);
+    x.set_b(0xBCD);
+    assert_eq!(x.get_a(), 0xA);
+    assert_eq!(x.get_b(), 0xBCD);
+    assert_eq!(x.into_bytes(), [0xBC, 0xDA]);
+
+    let mut x = Msb0Little::new();
+    x.set_a(0xA);
+    x.set_b(0xBCD);
+    assert_eq!(x.get_a(), 0xA);
+    assert_eq!(x.get_b(), 0xBCD);
+    assert_eq!(x.into_bytes(), [0xCD, 0xAB]);
+}
@@ -0,0 +1,32 @@
+// A bitfield can be reinterpreted from and to its raw storage without going
+// through the accessors one field at a time. from_bytes and into_bytes move
+// the [u8; N] in and out, while as_bytes and as_mut_bytes borrow it in place.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Packet {
+    kind: B4,
+    flags: B4,
+    length: B16,
+    checksum: B8,
+}
+
+fn main() {
+    let buffer = [0x21, 0x34, 0x12, 0xff];
+    let mut packet = Packet::from_bytes(buffer);
+    assert_eq!(packet.get_kind(), 0x1);
+    assert_eq!(packet.get_flags(), 0x2);
+    assert_eq!(packet.get_length(), 0x1234);
+    assert_eq!(packet.get_checksum(), 0xff);
+    assert_eq!(packet.as_bytes(), &buffer);
+
+    packet.as_mut_bytes()[3] = 0x5a;
+    assert_eq!(packet.get_checksum(), 0x5a);
+
+    packet.set_length(0xbeef);
+    assert_eq!(packet.into_bytes(), [0x21, 0xef, 0xbe, 0x5a]);
+
+    const EMPTY: Packet = Packet::from_bytes([0; 4]);
+    assert_eq!(EMPTY.as_bytes(), Packet::new().as_bytes());
+}
@@ -0,0 +1,57 @@
+// A value wider than its field cannot be stored without losing bits. The
+// set_*_checked setters report that as an error and leave the field untouched,
+// while the behavior of the plain setters is chosen per struct with
+// #[bitfield(overflow = "...")]: "panic" (the default), "truncate" which drops
+// the high bits, or "debug_assert" which only panics in debug builds.
+
+use bitfield::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[bitfield]
+pub struct Strict {
+    a: B3,
+    b: B5,
+}
+
+#[bitfield(overflow = "truncate")]
+pub struct Lossy {
+    a: B3,
+    b: B5,
+}
+
+#[bitfield(overflow = "debug_assert")]
+pub struct Debugged {
+    a: B3,
+    b: B5,
+}
+
+fn main() {
+    let mut strict = Strict::new();
+    strict.set_b(0b10101);
+    assert_eq!(strict.set_a_checked(0b111), Ok(()));
+    assert_eq!(strict.get_a(), 0b111);
+
+    let err = strict.set_a_checked(0b1000).unwrap_err();
+    assert_eq!(err.raw_value(), 0b1000);
+    assert_eq!(err.bits(), 3);
+    assert_eq!(err.to_string(), "value 0x8 does not fit in 3 bits");
+    assert_eq!(strict.get_a(), 0b111);
+    assert_eq!(strict.get_b(), 0b10101);
+
+    let result = catch_unwind(AssertUnwindSafe(|| strict.set_a(0b1000)));
+    assert!(result.is_err());
+    assert_eq!(strict.get_a(), 0b111);
+    assert_eq!(strict.get_b(), 0b10101);
+
+    let mut lossy = Lossy::new();
+    lossy.set_b(0b10101);
+    lossy.set_a(0b1101);
+    assert_eq!(lossy.get_a(), 0b101);
+    assert_eq!(lossy.get_b(), 0b10101);
+    assert!(lossy.set_a_checked(0b1101).is_err());
+
+    let mut debugged = Debugged::new();
+    let result = catch_unwind(AssertUnwindSafe(|| debugged.set_a(0b1000)));
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+    assert_eq!(debugged.get_b(), 0);
+}
@@ -0,0 +1,87 @@
+// Besides get_* and set_*, every field gets a consuming with_* method that
+// returns the updated value, so a register can be built in one expression.
+// For bool and B* fields the method is a const fn and can be used to define
+// constants and statics. The macro cannot see whether another field type was
+// derived, so fields of a derived BitfieldSpecifier enum or nested #[bitfield]
+// opt in with #[const_with]; fields of other specifier types, like Version
+// which implements Specifier by hand, go through set_*.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Config {
+    enable: bool,
+    mode: Mode,
+    divider: B6,
+    threshold: B8,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+pub enum Mode {
+    Slow,
+    Fast,
+}
+
+#[bitfield]
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+pub struct Flags {
+    ready: bool,
+    error: bool,
+    __: B6,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Version(u8);
+
+impl Specifier for Version {
+    const BITS: usize = 8;
+    type Bytes = u8;
+    type InOut = Version;
+    type Output = Version;
+
+    fn into_bytes(input: Version) -> u8 {
+        input.0
+    }
+
+    fn from_bytes(bytes: u8) -> Version {
+        Version(bytes)
+    }
+}
+
+#[bitfield]
+pub struct Reg {
+    enable: bool,
+    #[const_with]
+    mode: Mode,
+    #[const_with]
+    flags: Flags,
+    __: B6,
+    version: Version,
+}
+
+const DEFAULT: Config = Config::new().with_enable(true).with_divider(12);
+static TUNED: Config = DEFAULT.with_threshold(0x80);
+const CFG: Reg = Reg::new()
+    .with_enable(true)
+    .with_mode(Mode::Fast)
+    .with_flags(Flags::new().with_error(true));
+
+fn main() {
+    assert_eq!(DEFAULT.get_enable(), true);
+    assert_eq!(DEFAULT.get_mode(), Mode::Slow);
+    assert_eq!(DEFAULT.get_divider(), 12);
+    assert_eq!(DEFAULT.get_threshold(), 0);
+    assert_eq!(TUNED.get_threshold(), 0x80);
+
+    let config = DEFAULT.with_mode(Mode::Fast).with_enable(false);
+    assert_eq!(config.get_enable(), false);
+    assert_eq!(config.get_mode(), Mode::Fast);
+    assert_eq!(config.get_divider(), 12);
+
+    assert_eq!(CFG.get_enable(), true);
+    assert_eq!(CFG.get_mode(), Mode::Fast);
+    assert_eq!(CFG.get_flags(), Flags::new().with_error(true));
+    let reg = CFG.with_version(Version(3));
+    assert_eq!(reg.get_version(), Version(3));
+    assert_eq!(reg.get_mode(), Mode::Fast);
+}
@@ -0,0 +1,46 @@
+// A #[bitfield] struct no wider than 64 bits can itself be used as a member of
+// a larger bitfield. Writing #[derive(BitfieldSpecifier)] below #[bitfield]
+// implements Specifier for it, and the accessors of the outer struct take and
+// return the inner struct.
+
+use bitfield::*;
+
+#[bitfield]
+#[derive(BitfieldSpecifier, Clone, Copy, Debug, PartialEq)]
+pub struct Flags {
+    urgent: bool,
+    ack: bool,
+    push: bool,
+    reset: bool,
+    syn: bool,
+    fin: bool,
+    reserved: B2,
+}
+
+#[bitfield]
+pub struct Header {
+    source: B16,
+    #[bits = 8]
+    flags: Flags,
+    window: B8,
+}
+
+fn main() {
+    assert_eq!(<Flags as Specifier>::BITS, 8);
+    assert_eq!(std::mem::size_of::<Header>(), 4);
+
+    let mut header = Header::new();
+    assert_eq!(header.get_flags(), Flags::new());
+
+    header.set_source(0xbeef);
+    header.set_flags(Flags::new().with_ack(true).with_fin(true));
+    header.set_window(0x7f);
+
+    let flags = header.get_flags();
+    assert_eq!(flags.get_urgent(), false);
+    assert_eq!(flags.get_ack(), true);
+    assert_eq!(flags.get_fin(), true);
+    assert_eq!(header.get_source(), 0xbeef);
+    assert_eq!(header.get_window(), 0x7f);
+    assert_eq!(header.into_bytes(), [0xef, 0xbe, 0b0010_0010, 0x7f]);
+}
@@ -0,0 +1,50 @@
+// Deriving Debug, PartialEq or Hash on the generated struct would only look at
+// the raw byte array. When written below #[bitfield], these derives are taken
+// over by the attribute macro, which implements them field by field through
+// the getters. Debug (or CustomDebug) also honors #[debug = "..."] on a field.
+
+use bitfield::*;
+use std::collections::HashSet;
+
+#[bitfield]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Status {
+    ready: bool,
+    mode: Mode,
+    #[debug = "{:#04x}"]
+    code: B6,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Idle,
+    Busy,
+}
+
+#[bitfield]
+#[derive(CustomDebug)]
+pub struct Mask {
+    #[debug = "0b{:04b}"]
+    low: B4,
+    high: B4,
+}
+
+fn main() {
+    let status = Status::new().with_ready(true).with_code(0x2a);
+    assert_eq!(
+        format!("{:?}", status),
+        "Status { ready: true, mode: Idle, code: 0x2a }",
+    );
+
+    let mut other = Status::new();
+    assert_ne!(status, other);
+    other.set_ready(true);
+    other.set_code(0x2a);
+    assert_eq!(status, other);
+
+    let set: HashSet<Status> = [status, other, Status::new()].into_iter().collect();
+    assert_eq!(set.len(), 2);
+
+    let mask = Mask::new().with_low(0b0101).with_high(3);
+    assert_eq!(format!("{:?}", mask), "Mask { low: 0b0101, high: 3 }");
+}
@@ -0,0 +1,39 @@
+// Register layouts are full of reserved bits. A field marked #[skip], or named
+// with underscores only such as `__`, still occupies its bits but gets no
+// accessors. #[skip(getters)] and #[skip(setters)] drop only one kind of
+// accessor, making the field write-only or read-only. Fields without a getter
+// are left out of the generated Debug, PartialEq and Hash.
+
+use bitfield::*;
+
+#[bitfield]
+#[derive(Debug, PartialEq)]
+pub struct Control {
+    enable: bool,
+    #[skip]
+    reserved: B3,
+    #[skip(setters)]
+    revision: B4,
+    #[skip(getters)]
+    reset: bool,
+    __: B7,
+}
+
+fn main() {
+    assert_eq!(std::mem::size_of::<Control>(), 2);
+
+    let mut control = Control::from_bytes([0b1111_1111, 0b1111_1111]);
+    assert_eq!(control.get_enable(), true);
+    assert_eq!(control.get_revision(), 0b1111);
+
+    control.set_enable(false);
+    control.set_reset(false);
+    assert_eq!(control.as_bytes(), &[0b1111_1110, 0b1111_1110]);
+    assert_eq!(
+        format!("{:?}", control),
+        "Control { enable: false, revision: 15 }",
+    );
+
+    let other = Control::from_bytes([0b1111_0000, 0]);
+    assert_eq!(control, other);
+}
@@ -0,0 +1,21 @@
+// Skipped fields really have no accessors: reading a write-only field or
+// writing a read-only one does not compile.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Control {
+    #[skip(setters)]
+    revision: B4,
+    #[skip(getters)]
+    reset: bool,
+    #[skip]
+    reserved: B3,
+}
+
+fn main() {
+    let mut control = Control::new();
+    control.set_revision(1);
+    control.get_reset();
+    control.get_reserved();
+}
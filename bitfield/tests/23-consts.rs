@@ -0,0 +1,47 @@
+// Every #[bitfield] struct exposes its layout as associated consts: BITS and
+// BYTES for the whole struct, and <FIELD>_OFFSET, <FIELD>_BITS and <FIELD>_MASK
+// for each field. Offsets and masks treat the storage as one integer, the same
+// way the bit_order argument does, so they can be checked at compile time
+// against the values in a datasheet or a C header. Reserved fields named `__`
+// get no consts.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Status {
+    enable: bool,
+    mode: B3,
+    __: B4,
+    count: B8,
+}
+
+#[bitfield(bit_order = "msb0")]
+pub struct Header {
+    version: B4,
+    ihl: B4,
+}
+
+const _: () = assert!(Status::BITS == 16);
+const _: () = assert!(Status::BYTES == 2);
+const _: () = assert!(Status::MODE_OFFSET == 1);
+const _: () = assert!(Status::MODE_MASK == 0x000e);
+
+fn main() {
+    assert_eq!(Status::ENABLE_OFFSET, 0);
+    assert_eq!(Status::ENABLE_BITS, 1);
+    assert_eq!(Status::ENABLE_MASK, 0x0001);
+    assert_eq!(Status::MODE_BITS, 3);
+    assert_eq!(Status::COUNT_OFFSET, 8);
+    assert_eq!(Status::COUNT_BITS, 8);
+    assert_eq!(Status::COUNT_MASK, 0xff00);
+
+    assert_eq!(Header::VERSION_OFFSET, 4);
+    assert_eq!(Header::VERSION_MASK, 0xf0);
+    assert_eq!(Header::IHL_OFFSET, 0);
+    assert_eq!(Header::IHL_MASK, 0x0f);
+
+    let status = Status::new().with_mode(0b101).with_count(0x42);
+    let raw = u16::from_le_bytes(status.into_bytes()) as u64;
+    assert_eq!((raw & Status::MODE_MASK) >> Status::MODE_OFFSET, 0b101);
+    assert_eq!((raw & Status::COUNT_MASK) >> Status::COUNT_OFFSET, 0x42);
+}
@@ -0,0 +1,58 @@
+// A field may be an array of specifiers such as [B4; 8]. Its elements are laid
+// out one after another, and the accessors take the index of the element as
+// their first argument. iter_<field> walks all elements in order. An index out
+// of range panics like slice indexing does.
+
+use bitfield::*;
+
+#[derive(BitfieldSpecifier, Debug, PartialEq, Clone, Copy)]
+pub enum Flag {
+    Off = 0,
+    On = 1,
+}
+
+#[bitfield]
+#[derive(Debug, PartialEq)]
+pub struct Mixer {
+    lanes: [B4; 4],
+    enabled: [Flag; 8],
+    gain: B8,
+}
+
+#[bitfield(bit_order = "msb0")]
+pub struct Nibbles {
+    nibbles: [B4; 2],
+}
+
+fn main() {
+    assert_eq!(std::mem::size_of::<Mixer>(), 4);
+    assert_eq!(Mixer::LANES_LEN, 4);
+    assert_eq!(Mixer::LANES_BITS, 16);
+    assert_eq!(Mixer::ENABLED_OFFSET, 16);
+    assert_eq!(Mixer::ENABLED_MASK, 0x00ff_0000);
+
+    let mut mixer = Mixer::new().with_lanes(1, 0xa).with_gain(0x7f);
+    mixer.set_lanes(3, 0xf);
+    mixer.set_enabled(2, Flag::On);
+    assert_eq!(mixer.get_lanes(0), 0);
+    assert_eq!(mixer.get_lanes(1), 0xa);
+    assert_eq!(mixer.get_lanes(3), 0xf);
+    assert_eq!(mixer.get_enabled(2), Flag::On);
+    assert_eq!(mixer.get_enabled(3), Flag::Off);
+    assert_eq!(mixer.iter_lanes().collect::<Vec<_>>(), [0, 0xa, 0, 0xf]);
+    assert_eq!(mixer.as_bytes(), &[0xa0, 0xf0, 0b0000_0100, 0x7f]);
+    assert!(mixer.set_lanes_checked(0, 0x10).is_err());
+
+    assert_eq!(
+        format!("{:?}", Mixer::new().with_lanes(0, 1)),
+        "Mixer { lanes: [1, 0, 0, 0], enabled: [Off, Off, Off, Off, Off, Off, Off, Off], gain: 0 }",
+    );
+    assert_eq!(Mixer::new().with_lanes(2, 3), Mixer::new().with_lanes(2, 3));
+    assert_ne!(Mixer::new().with_lanes(2, 3), Mixer::new().with_lanes(1, 3));
+
+    let nibbles = Nibbles::new().with_nibbles(0, 0x1).with_nibbles(1, 0x2);
+    assert_eq!(nibbles.into_bytes(), [0x12]);
+
+    let result = std::panic::catch_unwind(|| Mixer::new().get_lanes(4));
+    assert!(result.is_err());
+}
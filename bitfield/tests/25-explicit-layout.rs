@@ -0,0 +1,43 @@
+// Instead of laying fields out one after another, every field can name the
+// bits it occupies with #[bits(start..end)] or #[bits(start..=last)], which
+// makes transcribing a datasheet straightforward. Fields may be declared in any
+// order. Bits are numbered the same way as bit_order, so bit 0 is the least
+// significant bit by default.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Control {
+    #[bits(12..16)]
+    prescaler: B4,
+    #[bits(0..=0)]
+    enable: bool,
+    #[bits(1..4)]
+    mode: B3,
+    #[skip]
+    #[bits(4..12)]
+    reserved: B8,
+}
+
+#[bitfield(bit_order = "msb0")]
+pub struct Header {
+    #[bits(4..8)]
+    ihl: B4,
+    #[bits(0..4)]
+    version: B4,
+}
+
+fn main() {
+    assert_eq!(Control::BITS, 16);
+    assert_eq!(Control::PRESCALER_OFFSET, 12);
+    assert_eq!(Control::MODE_MASK, 0b1110);
+
+    let control = Control::new()
+        .with_enable(true)
+        .with_mode(0b101)
+        .with_prescaler(0x9);
+    assert_eq!(control.into_bytes(), [0b0000_1011, 0x90]);
+
+    let header = Header::new().with_version(4).with_ihl(5);
+    assert_eq!(header.into_bytes(), [0x45]);
+}
@@ -0,0 +1,29 @@
+// With an explicit layout the ranges may not overlap, and every bit of the
+// storage has to belong to some field. Unused bits need a #[skip] field.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Overlap {
+    #[bits(0..4)]
+    low: B4,
+    #[bits(3..8)]
+    high: B5,
+}
+
+#[bitfield]
+pub struct Gap {
+    #[bits(0..4)]
+    low: B4,
+    #[bits(6..8)]
+    high: B2,
+}
+
+#[bitfield]
+pub struct Mixed {
+    #[bits(0..4)]
+    low: B4,
+    high: B4,
+}
+
+fn main() {}
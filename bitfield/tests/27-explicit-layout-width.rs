@@ -0,0 +1,14 @@
+// The width of a #[bits(start..end)] range is checked against the type of the
+// field in the same way as #[bits = N].
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Control {
+    #[bits(0..3)]
+    enable: bool,
+    #[bits(3..8)]
+    mode: B5,
+}
+
+fn main() {}
@@ -0,0 +1,71 @@
+// Register<T> wraps a pointer to a #[bitfield] struct living in device memory.
+// read, write and modify access the whole register with volatile operations,
+// using an integer of the register's width when there is one. A plain heap
+// buffer stands in for device memory here. Structs backed by an integer with
+// #[bitfield(repr = uN)] are read and written as that integer in native byte
+// order, whatever their endian argument says. That is why the Bitfield trait
+// names its conversions from_ne_bytes and into_ne_bytes: the struct's own
+// from_bytes and into_bytes follow the endian argument.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Control {
+    enable: bool,
+    mode: B3,
+    prescaler: B12,
+    divider: B16,
+}
+
+#[bitfield(repr = u32, endian = "big")]
+pub struct Status {
+    ready: bool,
+    code: B7,
+    count: B24,
+}
+
+#[bitfield]
+pub struct Wide {
+    low: B16,
+    high: B8,
+}
+
+fn main() {
+    let mut memory = Box::new(0u32);
+    let control = unsafe { Register::new(&mut *memory as *mut u32 as *mut Control) };
+
+    control.write(Control::new().with_mode(0b101).with_divider(0x1234));
+    let value = control.read();
+    assert_eq!(value.get_enable(), false);
+    assert_eq!(value.get_mode(), 0b101);
+    assert_eq!(value.get_divider(), 0x1234);
+
+    control.modify(|r| {
+        r.set_enable(true);
+        r.set_prescaler(0xabc);
+    });
+    let value = control.read();
+    assert_eq!(value.get_enable(), true);
+    assert_eq!(value.get_mode(), 0b101);
+    assert_eq!(value.get_prescaler(), 0xabc);
+    assert_eq!(*memory, u32::from_le_bytes(value.into_bytes()));
+
+    let mut memory = Box::new(0u32);
+    let status = unsafe { Register::new(&mut *memory as *mut u32 as *mut Status) };
+    status.write(Status::from_bits(0x42));
+    assert_eq!(*memory, 0x42);
+    assert_eq!(status.read().into_bits(), 0x42);
+    assert_eq!(status.read().into_bits(), unsafe { std::ptr::read(status.as_ptr()) }.into_bits());
+    status.modify(|r| r.set_count(0xabcdef));
+    assert_eq!(*memory, 0xabcd_ef42);
+    assert_eq!(status.read().get_code(), 0x21);
+    assert_eq!(Status::from_bits(0x42).into_bytes(), 0x42u32.to_be_bytes());
+    assert_eq!(Status::from_bits(0x42).into_ne_bytes(), 0x42u32.to_ne_bytes());
+    assert_eq!(Status::from_ne_bytes(0x42u32.to_ne_bytes()).into_bits(), 0x42);
+
+    let mut memory = [0u8; 3];
+    let wide = unsafe { Register::new(memory.as_mut_ptr() as *mut Wide) };
+    wide.modify(|r| r.set_high(0x7f));
+    assert_eq!(wide.read().get_high(), 0x7f);
+    assert_eq!(memory, [0, 0, 0x7f]);
+}
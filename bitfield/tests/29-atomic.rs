@@ -0,0 +1,65 @@
+// #[bitfield(atomic)] stores the fields in the narrowest atomic integer that
+// holds all of them. Accessors work through &self and take an Ordering, setters
+// are compare-and-swap loops that leave the other fields alone, and
+// fetch_update_<field> mirrors fetch_update on the standard atomic integers.
+
+use bitfield::*;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+#[bitfield(atomic)]
+#[derive(Debug)]
+pub struct Status {
+    ready: bool,
+    error: bool,
+    counters: [B4; 2],
+    pending: B6,
+    __: B2,
+    generation: B14,
+}
+
+fn main() {
+    assert_eq!(std::mem::size_of::<Status>(), 4);
+    assert_eq!(Status::GENERATION_OFFSET, 18);
+
+    let status = Arc::new(Status::new().with_generation(7));
+    let threads = (0..2)
+        .map(|index| {
+            let status = Arc::clone(&status);
+            thread::spawn(move || {
+                for _ in 0..15 {
+                    status
+                        .fetch_update_counters(index, Ordering::AcqRel, Ordering::Acquire, |n| {
+                            Some(n + 1)
+                        })
+                        .unwrap();
+                }
+                status.set_ready(true, Ordering::Release);
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(status.get_ready(Ordering::Acquire), true);
+    assert_eq!(status.get_counters(0, Ordering::Relaxed), 15);
+    assert_eq!(status.get_counters(1, Ordering::Relaxed), 15);
+    assert_eq!(status.get_generation(Ordering::Relaxed), 7);
+
+    let previous = status.fetch_update_pending(Ordering::SeqCst, Ordering::SeqCst, |_| None);
+    assert_eq!(previous, Err(0));
+    assert!(status.set_pending_checked(64, Ordering::Relaxed).is_err());
+    status.set_error(true, Ordering::Relaxed);
+    assert_eq!(status.load_bytes(Ordering::Relaxed), [0xff, 0x03, 0x1c, 0x00]);
+
+    let status = Status::from_bytes([0x01, 0, 0xa8, 0]);
+    assert_eq!(status.get_ready(Ordering::Relaxed), true);
+    assert_eq!(status.get_generation(Ordering::Relaxed), 42);
+    assert_eq!(
+        format!("{:?}", status),
+        "Status { ready: true, error: false, counters: [0, 0], pending: 0, generation: 42 }",
+    );
+    assert_eq!(status.into_bytes(), [0x01, 0, 0xa8, 0]);
+}
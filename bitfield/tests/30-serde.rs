@@ -0,0 +1,149 @@
+// With the serde feature enabled, Serialize and Deserialize written on a
+// #[bitfield] struct are taken over by the macro as well. Human-readable
+// formats see a map from field names to decoded values, BitfieldSpecifier enums
+// marked #[specifier(serde)] appear as their variant names, and unrecognized
+// bit patterns of sparse enums appear as raw numbers. Binary formats see the
+// storage bytes. Enums without #[specifier(serde)] get no serde impls from the
+// macro, so they are free to implement the traits themselves. Whatever is
+// serialized reads back: raw numbers are written back as raw bits, and
+// write-only fields, which are never serialized, may be left out.
+
+use bitfield::*;
+use serde::de::value::Error;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+#[specifier(serde)]
+pub enum Mode {
+    Idle = 0,
+    Run = 1,
+    Sleep = 2,
+    Halt = 3,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+#[bits = 2]
+#[specifier(serde)]
+pub enum Parity {
+    Even = 0,
+    Odd = 1,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+pub enum Level {
+    Low = 0,
+    High = 1,
+}
+
+impl serde::Serialize for Level {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(*self == Level::High)
+    }
+}
+
+#[bitfield]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    enable: bool,
+    mode: Mode,
+    parity: Parity,
+    __: B3,
+    channels: [B4; 2],
+    #[skip(setters)]
+    revision: B8,
+}
+
+#[bitfield]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Command {
+    a: B4,
+    #[skip(getters)]
+    cmd: B4,
+    lanes: [Parity; 4],
+}
+
+// A minimal non-human-readable deserializer handing out raw bytes.
+struct Binary<'a>(&'a [u8]);
+
+impl<'de> Deserializer<'de> for Binary<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bytes(self.0)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn main() {
+    let config = Config::new()
+        .with_enable(true)
+        .with_mode(Mode::Sleep)
+        .with_parity(Parity::Odd)
+        .with_channels(1, 9);
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(serde_json::to_string(&Level::High).unwrap(), "true");
+    assert_eq!(
+        json,
+        r#"{"enable":true,"mode":"Sleep","parity":"Odd","channels":[0,9],"revision":0}"#,
+    );
+
+    let decoded: Config = serde_json::from_str(
+        r#"{"mode":"Sleep","enable":true,"channels":[0,9],"parity":"Odd","revision":7}"#,
+    )
+    .unwrap();
+    assert_eq!(decoded, config);
+
+    let missing = serde_json::from_str::<Config>(r#"{"enable":true}"#);
+    assert!(missing.unwrap_err().to_string().contains("missing field `mode`"));
+    let unknown = serde_json::from_str::<Config>(r#"{"speed":1}"#);
+    assert!(unknown.unwrap_err().to_string().contains("unknown field `speed`"));
+    let too_wide = serde_json::from_str::<Config>(
+        r#"{"enable":true,"mode":"Run","parity":"Even","channels":[16,0]}"#,
+    );
+    assert!(too_wide.is_err());
+
+    let decoded: Config = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+    assert_eq!(decoded.get_revision(), 0);
+    assert_eq!(decoded, config);
+
+    let unrecognized = Config::from_bytes([0b0001_1000, 0, 0]);
+    let json = serde_json::to_value(&unrecognized).unwrap();
+    assert_eq!(json["parity"], 3);
+    let decoded: Config = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded.get_parity(), Err(Unrecognized::new(3)));
+    assert_eq!(decoded, unrecognized);
+    let too_wide = serde_json::from_str::<Config>(
+        r#"{"enable":true,"mode":"Run","parity":4,"channels":[0,0]}"#,
+    );
+    assert!(too_wide.is_err());
+
+    let command = Command::new()
+        .with_a(3)
+        .with_cmd(9)
+        .with_lanes(0, Parity::Odd)
+        .with_lanes(3, Parity::Odd);
+    let mut lanes = command.into_bytes();
+    lanes[1] |= 0b0000_1100;
+    let command = Command::from_bytes(lanes);
+    let json = serde_json::to_string(&command).unwrap();
+    assert_eq!(json, r#"{"a":3,"lanes":["Odd",3,"Even","Odd"]}"#);
+    let decoded: Command = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.get_a(), 3);
+    assert_eq!(decoded.get_lanes(1), Err(Unrecognized::new(3)));
+    assert_eq!(decoded.into_bytes(), Command::from_bytes(lanes).with_cmd(0).into_bytes());
+
+    let bytes = config.as_bytes();
+    let decoded = Config::deserialize(Binary(bytes)).unwrap();
+    assert_eq!(decoded, config);
+    assert!(Config::deserialize(Binary(&[0, 0])).is_err());
+}
@@ -0,0 +1,49 @@
+// I1 through I64 are two's-complement counterparts of the B types. Their
+// getters sign-extend into i8, i16, i32 or i64, and their setters only accept
+// values in -2^(N-1)..2^(N-1), reporting anything else like any other value
+// that does not fit.
+
+use bitfield::*;
+
+#[bitfield]
+#[derive(Debug)]
+pub struct Sample {
+    temperature: I12,
+    flags: B4,
+    offset: I3,
+    gain: I5,
+    wide: I64,
+}
+
+fn main() {
+    assert_eq!(<I12 as Specifier>::BITS, 12);
+    assert_eq!(std::mem::size_of::<Sample>(), 11);
+
+    let mut sample = Sample::new();
+    sample.set_temperature(-40);
+    sample.set_flags(0xf);
+    sample.set_offset(-4);
+    sample.set_gain(15);
+    sample.set_wide(i64::MIN);
+    assert_eq!(sample.get_temperature(), -40);
+    assert_eq!(sample.get_flags(), 0xf);
+    assert_eq!(sample.get_offset(), -4);
+    assert_eq!(sample.get_gain(), 15);
+    assert_eq!(sample.get_wide(), i64::MIN);
+    assert_eq!(&sample.as_bytes()[..2], &[0xd8, 0xff]);
+
+    sample.set_temperature(2047);
+    assert_eq!(sample.get_temperature(), 2047);
+    sample.set_temperature(-2048);
+    assert_eq!(sample.get_temperature(), -2048);
+    assert_eq!(sample.get_flags(), 0xf);
+
+    assert!(sample.set_temperature_checked(2048).is_err());
+    assert!(sample.set_temperature_checked(-2049).is_err());
+    assert!(sample.set_offset_checked(4).is_err());
+    assert!(sample.set_offset_checked(3).is_ok());
+    assert_eq!(sample.get_offset(), 3);
+
+    let result = std::panic::catch_unwind(|| Sample::new().with_gain(-17));
+    assert!(result.is_err());
+}
@@ -0,0 +1,48 @@
+// The size of the storage can be given explicitly with #[bitfield(bytes = N)].
+// The fields no longer need to add up to a multiple of 8 bits; the bits after
+// the last field are padding that always reads as zero. With
+// #[bitfield(repr = u32)] the storage is a plain integer instead of a byte
+// array, and the struct converts to and from that integer.
+
+use bitfield::*;
+
+#[bitfield(bytes = 4)]
+pub struct Padded {
+    a: B3,
+    b: B9,
+}
+
+#[bitfield(bytes = 2, bit_order = "msb0", endian = "big")]
+pub struct Flags {
+    high: B4,
+    next: B2,
+}
+
+#[bitfield(repr = u32)]
+#[derive(Debug, PartialEq)]
+pub struct Status {
+    ready: bool,
+    code: B7,
+    count: B12,
+}
+
+fn main() {
+    assert_eq!(std::mem::size_of::<Padded>(), 4);
+    assert_eq!(Padded::BITS, 32);
+    assert_eq!(Padded::BYTES, 4);
+    let mut padded = Padded::new();
+    padded.set_a(0b101);
+    padded.set_b(0x1ff);
+    assert_eq!(padded.into_bytes(), [0xfd, 0x0f, 0, 0]);
+
+    let flags = Flags::new().with_high(0b1001).with_next(0b11);
+    assert_eq!(flags.into_bytes(), [0b1001_1100, 0]);
+
+    assert_eq!(std::mem::size_of::<Status>(), 4);
+    let status = Status::new().with_ready(true).with_code(0x55).with_count(0xabc);
+    let bits: u32 = status.into();
+    assert_eq!(bits, 0x000a_bcab);
+    assert_eq!(Status::from(bits), Status::from_bits(0x000a_bcab));
+    assert_eq!(Status::from_bytes([0xab, 0xbc, 0x0a, 0]).get_count(), 0xabc);
+    assert_eq!(Status::from_bits(bits).into_bytes(), [0xab, 0xbc, 0x0a, 0]);
+}
@@ -0,0 +1,16 @@
+// An explicit storage size must be large enough to hold every field.
+
+use bitfield::*;
+
+#[bitfield(bytes = 1)]
+pub struct TooSmall {
+    a: B4,
+    b: B5,
+}
+
+#[bitfield(repr = u8, bytes = 1)]
+pub struct Both {
+    a: B8,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// Offsets and widths are available for every field of a struct wider than 64
+// bits, and so are the masks of fields that lie entirely within the low 64
+// bits. The masks are u64, so a field outside the low 64 bits has no usable
+// mask; see 35-wide-mask.rs.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Descriptor {
+    address: B48,
+    length: B16,
+    flags: B8,
+    tail: B24,
+}
+
+const _: () = assert!(Descriptor::BITS == 96);
+const _: () = assert!(Descriptor::LENGTH_MASK == 0xffff_0000_0000_0000);
+const _: () = assert!(Descriptor::FLAGS_OFFSET == 64);
+const _: () = assert!(Descriptor::TAIL_OFFSET == 72);
+
+fn main() {
+    assert_eq!(Descriptor::BYTES, 12);
+    assert_eq!(Descriptor::ADDRESS_MASK, 0xffff_ffff_ffff);
+    assert_eq!(Descriptor::TAIL_BITS, 24);
+}
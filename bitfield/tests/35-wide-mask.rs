@@ -0,0 +1,18 @@
+// The mask of a field outside the low 64 bits does not fit in a u64. Using it
+// is a compile error that names the field.
+
+use bitfield::*;
+
+#[bitfield]
+pub struct Descriptor {
+    address: B48,
+    length: B16,
+    flags: B8,
+    tail: B24,
+}
+
+const FLAGS: u64 = Descriptor::FLAGS_MASK;
+
+fn main() {
+    let _ = FLAGS;
+}
@@ -0,0 +1,46 @@
+// Tuple structs work too. Their fields are named by position: the first field
+// gets get_0, set_0, set_0_checked and with_0, and the constants are prefixed
+// with an underscore, as in _0_OFFSET, since a name cannot start with a digit.
+// A derived Debug prints the fields by position like a tuple struct.
+
+use bitfield::*;
+
+#[bitfield(repr = u32)]
+#[derive(Debug, PartialEq)]
+pub struct Register(B12, bool, #[skip] B3, [B4; 4]);
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+pub enum Mode {
+    Idle,
+    Run,
+}
+
+#[bitfield]
+pub struct Pair(#[const_with] Mode, B7);
+
+const RUNNING: Pair = Pair::new().with_0(Mode::Run).with_1(0x55);
+
+fn main() {
+    assert_eq!(Register::_0_OFFSET, 0);
+    assert_eq!(Register::_0_BITS, 12);
+    assert_eq!(Register::_1_OFFSET, 12);
+    assert_eq!(Register::_1_MASK, 1 << 12);
+    assert_eq!(Register::_3_OFFSET, 16);
+    assert_eq!(Register::_3_LEN, 4);
+
+    let mut register = Register::new().with_0(0xabc).with_3(2, 0x9);
+    register.set_1(true);
+    assert_eq!(register.get_0(), 0xabc);
+    assert_eq!(register.get_1(), true);
+    assert_eq!(register.get_3(2), 0x9);
+    assert!(register.set_0_checked(0x1000).is_err());
+    assert_eq!(
+        format!("{:?}", register),
+        "Register(2748, true, [0, 0, 9, 0])",
+    );
+    assert_eq!(register.into_bits(), 0x0900_1abc);
+
+    assert_eq!(RUNNING.get_0(), Mode::Run);
+    assert_eq!(RUNNING.get_1(), 0x55);
+    assert_eq!(RUNNING.into_bytes(), [0xab]);
+}
@@ -1,16 +1,41 @@
 #[test]
 fn tests() {
-    let _ = trybuild::TestCases::new();
-    //t.pass("tests/01-specifier-types.rs");
-    //t.pass("tests/02-storage.rs");
-    //t.pass("tests/03-accessors.rs");
-    //t.compile_fail("tests/04-multiple-of-8bits.rs");
-    //t.pass("tests/05-accessor-signatures.rs");
-    //t.pass("tests/06-enums.rs");
-    //t.pass("tests/07-optional-discriminant.rs");
-    //t.compile_fail("tests/08-non-power-of-two.rs");
-    //t.compile_fail("tests/09-variant-out-of-range.rs");
-    //t.pass("tests/10-bits-attribute.rs");
-    //t.compile_fail("tests/11-bits-attribute-wrong.rs");
-    //t.pass("tests/12-accessors-edge.rs");
+    let t = trybuild::TestCases::new();
+    t.pass("tests/01-specifier-types.rs");
+    t.pass("tests/02-storage.rs");
+    t.pass("tests/03-accessors.rs");
+    t.compile_fail("tests/04-multiple-of-8bits.rs");
+    t.pass("tests/05-accessor-signatures.rs");
+    t.pass("tests/06-enums.rs");
+    t.pass("tests/07-optional-discriminant.rs");
+    t.compile_fail("tests/08-non-power-of-two.rs");
+    t.compile_fail("tests/09-variant-out-of-range.rs");
+    t.pass("tests/10-bits-attribute.rs");
+    t.compile_fail("tests/11-bits-attribute-wrong.rs");
+    t.pass("tests/12-accessors-edge.rs");
+    t.pass("tests/13-custom-specifier.rs");
+    t.pass("tests/14-sparse-enum.rs");
+    t.pass("tests/15-bit-order.rs");
+    t.pass("tests/16-bytes.rs");
+    t.pass("tests/17-checked-setters.rs");
+    t.pass("tests/18-with-methods.rs");
+    t.pass("tests/19-nested.rs");
+    t.pass("tests/20-derives.rs");
+    t.pass("tests/21-skip.rs");
+    t.compile_fail("tests/22-skip-no-accessors.rs");
+    t.pass("tests/23-consts.rs");
+    t.pass("tests/24-arrays.rs");
+    t.pass("tests/25-explicit-layout.rs");
+    t.compile_fail("tests/26-explicit-layout-coverage.rs");
+    t.compile_fail("tests/27-explicit-layout-width.rs");
+    t.pass("tests/28-register.rs");
+    t.pass("tests/29-atomic.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/30-serde.rs");
+    t.pass("tests/31-signed.rs");
+    t.pass("tests/32-storage-override.rs");
+    t.compile_fail("tests/33-storage-override-too-small.rs");
+    t.pass("tests/34-wide-consts.rs");
+    t.compile_fail("tests/35-wide-mask.rs");
+    t.pass("tests/36-tuple-struct.rs");
 }
@@ -6,5 +6,62 @@ pub const PROC_ATTR_BUILDER: &str = "builder";
 /// builder标签的配置key
 pub const BUILDER_EACH_KEY: &str = "each";
 
+/// builder标签的缺省值key，`#[builder(default)]`或`#[builder(default = "expr")]`
+pub const BUILDER_DEFAULT_KEY: &str = "default";
+
+/// builder标签的构建器风格key，`#[builder(pattern = "owned")]`或`#[builder(pattern = "mutable")]`
+pub const BUILDER_PATTERN_KEY: &str = "pattern";
+
+/// 构建器风格key所支持的取值，`"clone"`即不写`pattern`时的默认风格
+pub const BUILDER_PATTERN_VALUES: &[&str] = &["owned", "mutable", "clone"];
+
+/// builder标签的校验函数key，`#[builder(validate = "path::to::check")]`在`build`返回前校验原结构体
+pub const BUILDER_VALIDATE_KEY: &str = "validate";
+
+/// builder标签的typestate key，`#[builder(typestate)]`在编译期检查必填字段
+pub const BUILDER_TYPESTATE_KEY: &str = "typestate";
+
+/// 字段上的builder标签所支持的key
+pub const BUILDER_FIELD_KEYS: &[&str] = &[BUILDER_EACH_KEY, BUILDER_DEFAULT_KEY];
+
+/// 结构体上的builder标签所支持的key
+pub const BUILDER_STRUCT_KEYS: &[&str] = &[
+    BUILDER_DEFAULT_KEY,
+    BUILDER_PATTERN_KEY,
+    BUILDER_VALIDATE_KEY,
+    BUILDER_TYPESTATE_KEY,
+];
+
+/// builder标签中各key所接受的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyShape {
+    /// 只能单独出现，例如`typestate`
+    Path,
+    /// 只能带有字符串值，例如`each = "arg"`；给出的取值非空时，字符串只能是其中之一
+    Str(&'static [&'static str]),
+    /// 单独出现或带有字符串值均可，例如`default`与`default = "expr"`
+    PathOrStr,
+}
+
+/// 字段上的builder标签中各key所接受的写法
+pub const BUILDER_FIELD_SHAPES: &[(&str, KeyShape)] = &[
+    (BUILDER_EACH_KEY, KeyShape::Str(&[])),
+    (BUILDER_DEFAULT_KEY, KeyShape::PathOrStr),
+];
+
+/// 结构体上的builder标签中各key所接受的写法
+pub const BUILDER_STRUCT_SHAPES: &[(&str, KeyShape)] = &[
+    (BUILDER_DEFAULT_KEY, KeyShape::Path),
+    (BUILDER_PATTERN_KEY, KeyShape::Str(BUILDER_PATTERN_VALUES)),
+    (BUILDER_VALIDATE_KEY, KeyShape::Str(&[])),
+    (BUILDER_TYPESTATE_KEY, KeyShape::Path),
+];
+
 /// Builder结构体附加后缀
 pub const BUILDER_SUFFIX: &str = "Builder";
+
+/// 构建失败时错误枚举的附加后缀
+pub const BUILDER_ERROR_SUFFIX: &str = "BuilderError";
+
+/// 错误枚举引用`alloc`时所用别名的附加后缀
+pub const BUILDER_ALLOC_SUFFIX: &str = "BuilderAlloc";
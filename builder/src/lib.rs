@@ -12,12 +12,12 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
-use syn::spanned::Spanned;
 use syn::{parse_macro_input, DeriveInput};
 
 #[macro_use]
 mod macros;
 mod config;
+mod typestate;
 mod utils;
 
 /// 为结构体生成`Builder`方法  
@@ -59,7 +59,21 @@ mod utils;
 /// }
 ///
 /// ```
-
+///
+/// `build`缺少必填字段时返回生成的`CommandBuilderError`，其中每个必填字段对应一个`Missing`变体。
+/// 结构体上的`#[builder(validate = "path::to::check")]`指定一个`fn(&Command) -> Result<(), String>`，
+/// `build`在返回前调用它，失败时返回携带其说明的`Validation`变体
+///
+/// 字段上的`#[builder(default)]`与`#[builder(default = "expr")]`为字段提供缺省值，
+/// 结构体上的`#[builder(default)]`使未设置的字段取自原结构体的`Default::default()`
+///
+/// 结构体上的`#[builder(pattern = "owned")]`使setter按值接收并返回构建器、`build(self)`移动各字段；
+/// `#[builder(pattern = "mutable")]`使`build(&mut self)`用`take()`取走各字段。两者都不要求字段实现`Clone`；
+/// `#[builder(pattern = "clone")]`显式选择默认的克隆风格
+///
+/// 结构体上的`#[builder(typestate)]`在编译期检查必填字段：构建器对每个必填字段带有一个状态参数，
+/// 必填字段只能设置一次，`build`只在所有必填字段都已设置后存在并直接返回原结构体。
+/// 不写该标签时依然在`build`运行时检查并返回`CommandBuilderError`
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
@@ -71,6 +85,17 @@ pub fn derive(input: TokenStream) -> TokenStream {
             .unwrap_or_else(syn::Error::into_compile_error)
             .to_tokens(&mut ret)
     };
+    if config.typestate {
+        // Generate the state markers and the builder struct generic over them
+        append(Generator::generate_typestate_struct);
+        // Generate the builder impl
+        append(Generator::generate_typestate_impl);
+        // Generate the setters moving between states
+        append(Generator::generate_typestate_setter);
+        // Generate the build func for the state where every required field is set
+        append(Generator::generate_typestate_build);
+        return ret.into();
+    }
     // Generate the builder struct
     append(Generator::generate_builder_struct);
     // Generate the builder impl
@@ -79,6 +104,8 @@ pub fn derive(input: TokenStream) -> TokenStream {
     append(Generator::generate_builder_setter);
     // Generate the builder build func
     append(Generator::generate_builder_build);
+    // Generate the error returned by build
+    append(Generator::generate_builder_error);
 
     ret.into()
 }
@@ -94,8 +121,72 @@ struct BuilderConfig<'a> {
     vis: &'a syn::Visibility,
     /// 构建器字段
     fields: Vec<&'a syn::Field>,
-    /// 构建器泛型
+    /// 原结构体的泛型参数与where子句，构建器结构体及其所有impl沿用它们
     generics: &'a syn::Generics,
+    /// 结构体上的`#[builder(default)]`，未设置的字段取自原结构体的`Default::default()`
+    default: bool,
+    /// 结构体上的`#[builder(pattern = "...")]`，决定setter与`build`如何持有构建器
+    pattern: Pattern,
+    /// 结构体上的`#[builder(typestate)]`，在编译期检查必填字段是否都已设置
+    typestate: bool,
+    /// 结构体上的`#[builder(validate = "...")]`，`build`返回前调用的校验函数
+    validate: Option<syn::ExprPath>,
+}
+
+/// 构建器风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    /// 默认风格，也可以显式写作`pattern = "clone"`，setter接收并返回`&mut Self`，`build(&self)`克隆每个字段
+    Clone,
+    /// `pattern = "mutable"`，setter接收并返回`&mut Self`，`build(&mut self)`取走每个字段
+    Mutable,
+    /// `pattern = "owned"`，setter接收并返回`Self`，`build(self)`移动每个字段
+    Owned,
+}
+
+impl Pattern {
+    /// 解析`pattern = "..."`的值
+    fn parse(lit_str: &syn::LitStr) -> syn::Result<Self> {
+        match lit_str.value().as_str() {
+            "clone" => Ok(Self::Clone),
+            "mutable" => Ok(Self::Mutable),
+            "owned" => Ok(Self::Owned),
+            other => Err(syn::Error::new(
+                lit_str.span(),
+                format!(
+                    "未知的构建器风格{}，只支持{}",
+                    other,
+                    config::BUILDER_PATTERN_VALUES.join("、")
+                ),
+            )),
+        }
+    }
+
+    /// setter的接收者与返回类型
+    fn setter_signature(self) -> (TokenStream2, TokenStream2) {
+        match self {
+            Self::Clone | Self::Mutable => (quote!(&mut self), quote!(&mut Self)),
+            Self::Owned => (quote!(mut self), quote!(Self)),
+        }
+    }
+
+    /// `build`的接收者
+    fn build_receiver(self) -> TokenStream2 {
+        match self {
+            Self::Clone => quote!(&self),
+            Self::Mutable => quote!(&mut self),
+            Self::Owned => quote!(self),
+        }
+    }
+
+    /// `build`中取出构建器字段`ident`的表达式，类型为`Option`
+    fn take_field(self, ident: Option<&syn::Ident>) -> TokenStream2 {
+        match self {
+            Self::Clone => quote!(self.#ident.clone()),
+            Self::Mutable => quote!(self.#ident.take()),
+            Self::Owned => quote!(self.#ident),
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a syn::DeriveInput> for BuilderConfig<'a> {
@@ -111,12 +202,80 @@ impl<'a> TryFrom<&'a syn::DeriveInput> for BuilderConfig<'a> {
         } else {
             return Err(Self::Error::new(name.span(), "目前仅支持具名结构体"));
         };
+        // 字段标签中的未知属性与写法不符的属性在解析阶段就报错，生成时不再检查
+        for attr in fields
+            .iter()
+            .flat_map(|f| &f.attrs)
+            .filter(|a| a.path().is_ident(config::PROC_ATTR_BUILDER))
+        {
+            utils::builder_attribute_items(attr)?;
+            if let Some(ident) = utils::unknown_attribute_key(attr, config::BUILDER_FIELD_KEYS) {
+                return Err(Self::Error::new(
+                    ident.span(),
+                    format!(
+                        "builder标签只支持{}属性,当前有一个未知属性{}",
+                        config::BUILDER_FIELD_KEYS.join("、"),
+                        ident
+                    ),
+                ));
+            }
+            utils::check_attribute_shapes(attr, config::BUILDER_FIELD_SHAPES)?;
+        }
+        let mut default = false;
+        let mut pattern = Pattern::Clone;
+        let mut typestate = false;
+        let mut validate = None;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident(config::PROC_ATTR_BUILDER))
+        {
+            utils::builder_attribute_items(attr)?;
+            if let Some(ident) = utils::unknown_attribute_key(attr, config::BUILDER_STRUCT_KEYS) {
+                return Err(Self::Error::new(
+                    ident.span(),
+                    format!(
+                        "结构体上的builder标签只支持{}属性,当前有一个未知属性{}",
+                        config::BUILDER_STRUCT_KEYS.join("、"),
+                        ident
+                    ),
+                ));
+            }
+            utils::check_attribute_shapes(attr, config::BUILDER_STRUCT_SHAPES)?;
+            default |= utils::has_path_attribute(attr, config::BUILDER_DEFAULT_KEY);
+            if let Some(lit_str) = utils::unwrap_single_attribute(attr, config::BUILDER_PATTERN_KEY) {
+                pattern = Pattern::parse(&lit_str)?;
+            }
+            typestate |= utils::has_path_attribute(attr, config::BUILDER_TYPESTATE_KEY);
+            if let Some(lit_str) = utils::unwrap_single_attribute(attr, config::BUILDER_VALIDATE_KEY)
+            {
+                validate = Some(lit_str.parse::<syn::ExprPath>()?);
+            }
+        }
+        // typestate构建器的setter总是按值接收并返回构建器
+        if typestate && pattern == Pattern::Mutable {
+            return Err(Self::Error::new(
+                name.span(),
+                "typestate构建器总是按值传递，不能与pattern = \"mutable\"同时使用",
+            ));
+        }
+        // typestate构建器的build直接返回原结构体，没有可以报告校验失败的错误
+        if let (true, Some(validate)) = (typestate, &validate) {
+            return Err(Self::Error::new_spanned(
+                validate,
+                "typestate构建器的build不返回错误，不能与validate同时使用",
+            ));
+        }
 
         Ok(Self {
             name,
             vis,
             fields,
             generics,
+            default,
+            pattern,
+            typestate,
+            validate,
         })
     }
 }
@@ -148,6 +307,8 @@ impl Generator {
     pub fn generate_builder_struct(config: &BuilderConfig) -> syn::Result<TokenStream2> {
         let struct_name = utils::get_builder_struct_ident(config.name);
         let vis = config.vis;
+        let generics = config.generics;
+        let where_clause = &config.generics.where_clause;
         let fields = config.fields.iter().map(|field| {
             let field_name = field.ident.as_ref();
             let ty = &field.ty;
@@ -162,7 +323,7 @@ impl Generator {
             }
         });
         Ok(quote! {
-            #vis struct #struct_name{
+            #vis struct #struct_name #generics #where_clause {
                 #(#fields)*
             }
         })
@@ -208,6 +369,7 @@ impl Generator {
         let impl_struct_name = config.name;
         let builder_struct_name = utils::get_builder_struct_ident(config.name);
         let vis = config.vis;
+        let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
         let fields = config.fields.iter().map(|f| {
             let field_name = f.ident.as_ref();
             quote! {
@@ -215,8 +377,8 @@ impl Generator {
             }
         });
         Ok(quote! {
-            impl #impl_struct_name {
-                #vis fn builder() -> #builder_struct_name {
+            impl #impl_generics #impl_struct_name #ty_generics #where_clause {
+                #vis fn builder() -> #builder_struct_name #ty_generics {
                     #builder_struct_name {
                         #(#fields)*
                     }
@@ -228,6 +390,7 @@ impl Generator {
     /// 生成Builder的setter方法
     pub fn generate_builder_setter(config: &BuilderConfig) -> syn::Result<TokenStream2> {
         let impl_struct_name = utils::get_builder_struct_ident(config.name);
+        let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
         let fields = config.fields.iter().map(|f| {
             //可见性
             let vis = config.vis;
@@ -236,10 +399,7 @@ impl Generator {
             //字段名
             let field_name = f.ident.as_ref();
             //字段标签builder
-            let attrs = f
-                .attrs
-                .iter()
-                .find(|a| a.path().is_ident(config::PROC_ATTR_BUILDER));
+            let attrs = Self::field_builder_attr(f);
             // 如果是Option类型，就拿出内部类型，如果不是，就沿用类型
             let ty = {
                 let ty = &f.ty;
@@ -260,48 +420,46 @@ impl Generator {
                         //判断方法是否重复
                         let fn_is_repeat = fn_name == lit_str.value().as_str();
                         if !fn_is_repeat {
-                            ret = Self::generate_each_arg_setter(vis, &lit_str, f)
+                            ret = Self::generate_each_arg_setter(config, &lit_str, f)
                         }
-                    } else {
-                        let ident: Option<syn::Ident> =
-                            utils::meta_name_value_attr_left_value(attr);
-                        let err = syn::Error::new(
-                            attr.span(),
-                            format!(
-                                "builder标签必须有{}属性,当前有一个未知属性{}",
-                                config::BUILDER_EACH_KEY,
-                                ident.unwrap()
-                            ),
-                        );
-                        panic!("{}", err)
                     }
                 }
                 ret
             };
+            let (receiver, ret) = config.pattern.setter_signature();
             // 生成每个字段的setter方法
             // setter是一个Option，所以可以直接放入这里，如果和方法名重复了那么它就为None。
             quote! {
                 #setter
 
-                #vis fn #fn_name(&mut self,#field_name:#ty)->&mut Self{
+                #vis fn #fn_name(#receiver,#field_name:#ty)->#ret{
                     self.#field_name = Some(#field_name);
                     self
                 }
             }
         });
         Ok(quote! {
-            impl #impl_struct_name {
+            impl #impl_generics #impl_struct_name #ty_generics #where_clause {
                 #(#fields)*
             }
         })
     }
+    /// 取出字段上的builder标签
+    fn field_builder_attr(f: &syn::Field) -> Option<&syn::Attribute> {
+        f.attrs
+            .iter()
+            .find(|a| a.path().is_ident(config::PROC_ATTR_BUILDER))
+    }
+
     /// 用于解析 `#[builder(each = "arg")]` 标签并生成对应方法
     /// 它的内部必须是Vec
     pub fn generate_each_arg_setter(
-        vis: &syn::Visibility,
+        config: &BuilderConfig,
         lit_str: &syn::LitStr,
         f: &syn::Field,
     ) -> Option<TokenStream2> {
+        let vis = config.vis;
+        let (receiver, ret) = config.pattern.setter_signature();
         let ty = &f.ty;
         if utils::is_vec(ty) {
             let ty = utils::inner_type(ty);
@@ -309,7 +467,7 @@ impl Generator {
             let fn_name = syn::Ident::new(&lit_str.value(), lit_str.span());
             let fn_argument = &fn_name;
             return Some(quote! {
-                #vis fn #fn_name(&mut self,#fn_argument:#ty)->&mut Self{
+                #vis fn #fn_name(#receiver,#fn_argument:#ty)->#ret{
                     match self.#field_name{
                         None =>{self.#field_name = Some(vec![#fn_argument])}
                         Some(ref mut v) =>{v.push(#fn_argument)}
@@ -326,15 +484,28 @@ impl Generator {
     /// ```ignore
     /// x:self.x.clone(),
     /// ```
+    /// 带有`#[builder(default)]`或`#[builder(default = "expr")]`的字段未设置时取缺省值
+    /// ```ignore
+    /// x:self.x.clone().unwrap_or_else(|| core::default::Default::default()),
+    /// ```
+    /// `pattern = "mutable"`时`build(&mut self)`先检查必填字段再用`take()`取走各字段，
+    /// `pattern = "owned"`时`build(self)`直接移动各字段，两者都不要求字段实现`Clone`；
+    /// 默认风格的`build`带有`where x的类型: Clone`约束，见[`Generator::build_bound`]
+    ///
+    /// 结构体上有`#[builder(default)]`时，未设置的字段取自原结构体的`Default::default()`
+    /// ```ignore
+    /// let __default = <Command as core::default::Default>::default();
+    /// x:self.x.clone().unwrap_or_else(|| __default.x),
+    /// ```
     /// ---
     /// ```ignore
     /// impl CommandBuilder {
-    ///       pub fn build(&self) -> core::result::Result<Command, Box<dyn core::error::Error>> {
+    ///       pub fn build(&self) -> core::result::Result<Command, CommandBuilderError> {
     ///           Command {
-    ///               executable: self.executable.clone().ok_or("struct Command not set field executable")?,
-    ///               args: self.args.clone().ok_or("struct Command not set field args")?,
-    ///               env: self.env.clone().ok_or("struct Command not set field env")?,
-    ///               current_dir: self.current_dir.clone().ok_or("struct Command not set field current_dir")?,
+    ///               executable: self.executable.clone().ok_or(CommandBuilderError::MissingExecutable)?,
+    ///               args: self.args.clone().ok_or(CommandBuilderError::MissingArgs)?,
+    ///               env: self.env.clone().ok_or(CommandBuilderError::MissingEnv)?,
+    ///               current_dir: self.current_dir.clone(),
     ///           }
     ///       }
     ///   }
@@ -342,36 +513,191 @@ impl Generator {
     pub fn generate_builder_build(config: &BuilderConfig) -> syn::Result<TokenStream2> {
         let src_struct_name = config.name;
         let impl_struct_name = utils::get_builder_struct_ident(config.name);
+        let error_name = utils::get_builder_error_ident(config.name);
         let vis = config.vis;
+        let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
 
-        let fields = config.fields.iter().map(|f| {
-            let ident = f.ident.as_ref();
-            let ty = &f.ty;
-            let err_msg = format!(
-                "struct {} not set field {}",
-                src_struct_name,
-                ident.unwrap()
-            );
-            // 判断字段是否是option
-            if utils::is_option(ty) {
-                quote! {
-                    #ident: self.#ident.clone()
-                }
-            } else {
-                quote! {
-                    #ident: self.#ident.clone().ok_or(#err_msg)?,
+        let fields = Self::build_fields(config, config.pattern, |ident| {
+            let variant = utils::get_missing_variant_ident(ident);
+            quote!(.ok_or(#error_name::#variant)?)
+        })?;
+        // take()会清空构建器，需要在取走任何字段之前确认必填字段都已设置
+        let checks = if config.pattern == Pattern::Mutable {
+            config
+                .fields
+                .iter()
+                .filter(|f| utils::is_required(f, config.default))
+                .map(|f| {
+                    let ident = f.ident.as_ref();
+                    let variant = utils::get_missing_variant_ident(ident.unwrap());
+                    quote! {
+                        if self.#ident.is_none() {
+                            return core::result::Result::Err(#error_name::#variant);
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        let receiver = config.pattern.build_receiver();
+        let struct_default = Self::struct_default(config);
+        let build_bound = Self::build_bound(config, config.pattern);
+        let validate = config.validate.as_ref().map(|validate| {
+            quote! {
+                if let core::result::Result::Err(message) = #validate(&__built) {
+                    return core::result::Result::Err(#error_name::Validation(message));
                 }
             }
         });
 
         Ok(quote! {
-            impl #impl_struct_name {
-                #vis fn build(&self)->core::result::Result<#src_struct_name,std::boxed::Box<dyn core::error::Error>>{
-                    Ok(#src_struct_name{
+            impl #impl_generics #impl_struct_name #ty_generics #where_clause {
+                #vis fn build(#receiver)->core::result::Result<#src_struct_name #ty_generics,#error_name> #build_bound {
+                    #(#checks)*
+                    #struct_default
+                    let __built = #src_struct_name{
                         #(#fields)*
-                    })
+                    };
+                    #validate
+                    core::result::Result::Ok(__built)
                 }
             }
         })
     }
+
+    /// 生成`build`中原结构体每个字段的赋值，构建器字段按`pattern`取出
+    /// 必填字段未设置时的处理由`missing`给出，它接在类型为`Option`的取值表达式之后
+    fn build_fields(
+        config: &BuilderConfig,
+        pattern: Pattern,
+        missing: impl Fn(&syn::Ident) -> TokenStream2,
+    ) -> syn::Result<Vec<TokenStream2>> {
+        config
+            .fields
+            .iter()
+            .map(|f| {
+                let ident = f.ident.as_ref();
+                let ty = &f.ty;
+                let value = pattern.take_field(ident);
+                let default = match utils::field_default(f)? {
+                    Some(default) => Some(default),
+                    None if config.default => Some(quote!(__default.#ident)),
+                    None => None,
+                };
+                // 判断字段是否是option
+                Ok(match (utils::is_option(ty), default) {
+                    (true, Some(default)) => quote! {
+                        #ident: #value.or_else(|| #default),
+                    },
+                    (true, None) => quote! {
+                        #ident: #value,
+                    },
+                    (false, Some(default)) => quote! {
+                        #ident: #value.unwrap_or_else(|| #default),
+                    },
+                    (false, None) => {
+                        let missing = missing(ident.unwrap());
+                        quote! {
+                            #ident: #value #missing,
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// 结构体上有`#[builder(default)]`时，`build`开头取得的原结构体缺省值
+    fn struct_default(config: &BuilderConfig) -> TokenStream2 {
+        let src_struct_name = config.name;
+        let (_, ty_generics, _) = config.generics.split_for_impl();
+        if config.default {
+            quote! {
+                let __default = <#src_struct_name #ty_generics as core::default::Default>::default();
+            }
+        } else {
+            quote!()
+        }
+    }
+
+    /// `build`方法自身的where子句
+    /// 默认风格的`build(&self)`克隆每个字段，要求字段类型实现Clone；结构体级别的缺省值要求原结构体实现Default。
+    /// 约束写在`build`上而不是impl上，因此构建器总能生成，只有调用`build`时才检查这些约束
+    fn build_bound(config: &BuilderConfig, pattern: Pattern) -> TokenStream2 {
+        let src_struct_name = config.name;
+        let (_, ty_generics, _) = config.generics.split_for_impl();
+        let mut predicates = Vec::new();
+        if pattern == Pattern::Clone {
+            predicates.extend(config.fields.iter().map(|f| {
+                let ty = &f.ty;
+                quote!(#ty: core::clone::Clone)
+            }));
+        }
+        if config.default {
+            predicates.push(quote!(#src_struct_name #ty_generics: core::default::Default));
+        }
+        if predicates.is_empty() {
+            quote!()
+        } else {
+            quote!(where #(#predicates),*)
+        }
+    }
+
+    /// 生成`build`失败时返回的错误枚举
+    /// 每个必填字段（没有缺省值的非[`Option`]字段）对应一个`Missing`变体，
+    /// 另有一个携带`validate`校验函数所返回说明的`Validation`变体
+    /// 错误枚举只引用`core`与`alloc`中的内容，可以用于带有`alloc`的`no_std`环境
+    /// ```ignore
+    /// extern crate alloc as __CommandBuilderAlloc;
+    /// #[derive(Debug, Clone, PartialEq, Eq)]
+    /// pub enum CommandBuilderError {
+    ///     MissingExecutable,
+    ///     MissingArgs,
+    ///     MissingEnv,
+    ///     Validation(__CommandBuilderAlloc::string::String),
+    /// }
+    /// impl core::fmt::Display for CommandBuilderError { ... }
+    /// impl core::error::Error for CommandBuilderError {}
+    /// ```
+    pub fn generate_builder_error(config: &BuilderConfig) -> syn::Result<TokenStream2> {
+        let src_struct_name = config.name;
+        let error_name = utils::get_builder_error_ident(config.name);
+        let alloc = utils::get_builder_alloc_ident(config.name);
+        let vis = config.vis;
+        let (variants, messages): (Vec<_>, Vec<_>) = config
+            .fields
+            .iter()
+            .filter(|f| utils::is_required(f, config.default))
+            .filter_map(|f| f.ident.as_ref())
+            .map(|ident| {
+                let variant = utils::get_missing_variant_ident(ident);
+                let message = format!("struct {} not set field {}", src_struct_name, ident);
+                (variant, message)
+            })
+            .unzip();
+        let doc = format!("`{}`构建失败的原因", src_struct_name);
+
+        Ok(quote! {
+            #[doc(hidden)]
+            extern crate alloc as #alloc;
+
+            #[doc = #doc]
+            #[derive(core::fmt::Debug, core::clone::Clone, core::cmp::PartialEq, core::cmp::Eq)]
+            #vis enum #error_name {
+                #(#variants,)*
+                Validation(#alloc::string::String),
+            }
+
+            impl core::fmt::Display for #error_name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    match self {
+                        #(Self::#variants => f.write_str(#messages),)*
+                        Self::Validation(message) => f.write_str(message),
+                    }
+                }
+            }
+
+            impl core::error::Error for #error_name {}
+        })
+    }
 }
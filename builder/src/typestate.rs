@@ -0,0 +1,327 @@
+//! `#[builder(typestate)]`的生成逻辑
+//!
+//! 构建器对每个必填字段带有一个泛型参数，取值为`XBuilderSet`或`XBuilderUnset`，
+//! 必填字段的setter只在该字段未设置时存在，`build`只在所有必填字段都已设置时存在
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::{config, utils, BuilderConfig, Generator, Pattern};
+
+/// typestate构建器所需的泛型信息
+struct States<'a> {
+    /// 原结构体的泛型参数，去掉了缺省值
+    params: Vec<syn::GenericParam>,
+    /// 原结构体泛型参数在使用处的写法
+    args: Vec<TokenStream2>,
+    /// 原结构体的where子句
+    where_clause: &'a Option<syn::WhereClause>,
+    /// 每个字段对应的状态参数，非必填字段为`None`
+    states: Vec<Option<syn::Ident>>,
+}
+
+impl<'a> States<'a> {
+    fn new(config: &'a BuilderConfig) -> Self {
+        let params = config
+            .generics
+            .params
+            .iter()
+            .cloned()
+            .map(|mut param| {
+                match param {
+                    syn::GenericParam::Type(ref mut param) => {
+                        param.eq_token = None;
+                        param.default = None;
+                    }
+                    syn::GenericParam::Const(ref mut param) => {
+                        param.eq_token = None;
+                        param.default = None;
+                    }
+                    syn::GenericParam::Lifetime(_) => {}
+                }
+                param
+            })
+            .collect();
+        let states = config
+            .fields
+            .iter()
+            .map(|f| {
+                utils::is_required(f, config.default)
+                    .then(|| f.ident.as_ref().map(utils::get_state_param_ident))
+                    .flatten()
+            })
+            .collect();
+        Self {
+            params,
+            args: utils::generic_args(config.generics),
+            where_clause: &config.generics.where_clause,
+            states,
+        }
+    }
+
+    /// 所有状态参数
+    fn params(&self) -> impl Iterator<Item = &syn::Ident> {
+        self.states.iter().flatten()
+    }
+
+    /// 除第`skip`个字段以外的状态参数
+    fn params_except(&self, skip: usize) -> impl Iterator<Item = &syn::Ident> {
+        self.states
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| *index != skip)
+            .filter_map(|(_, state)| state.as_ref())
+    }
+
+    /// 构建器类型的状态实参，第`index`个字段取`replace`，其余沿用状态参数
+    fn args_with<'b>(&'b self, index: usize, replace: &'b syn::Ident) -> Vec<&'b syn::Ident> {
+        self.states
+            .iter()
+            .enumerate()
+            .filter_map(|(i, state)| {
+                state
+                    .as_ref()
+                    .map(|state| if i == index { replace } else { state })
+            })
+            .collect()
+    }
+}
+
+impl Generator {
+    /// 生成状态标记类型以及带有状态参数的Builder结构体
+    /// ```ignore
+    /// pub struct CommandBuilderSet;
+    /// pub struct CommandBuilderUnset;
+    /// pub struct CommandBuilder<__Executable> {
+    ///     executable: core::option::Option<String>,
+    ///     current_dir: core::option::Option<String>,
+    ///     __state: core::marker::PhantomData<(__Executable,)>,
+    /// }
+    /// ```
+    pub fn generate_typestate_struct(config: &BuilderConfig) -> syn::Result<TokenStream2> {
+        let struct_name = utils::get_builder_struct_ident(config.name);
+        let (set, unset) = utils::get_state_marker_idents(config.name);
+        let vis = config.vis;
+        let states = States::new(config);
+        let params = &states.params;
+        let where_clause = states.where_clause;
+        let state_params = states.params().collect::<Vec<_>>();
+        let fields = config.fields.iter().map(|field| {
+            let field_name = field.ident.as_ref();
+            let ty = &field.ty;
+            if utils::is_option(ty) {
+                quote! {
+                    #field_name: #ty,
+                }
+            } else {
+                quote! {
+                    #field_name: core::option::Option<#ty>,
+                }
+            }
+        });
+        let set_doc = format!("`{}`的必填字段已设置", struct_name);
+        let unset_doc = format!("`{}`的必填字段尚未设置", struct_name);
+        Ok(quote! {
+            #[doc = #set_doc]
+            #vis struct #set;
+
+            #[doc = #unset_doc]
+            #vis struct #unset;
+
+            #vis struct #struct_name<#(#params,)* #(#state_params,)*> #where_clause {
+                #(#fields)*
+                __state: core::marker::PhantomData<(#(#state_params,)*)>,
+            }
+        })
+    }
+
+    /// 生成`builder`方法，返回所有必填字段都未设置的构建器
+    pub fn generate_typestate_impl(config: &BuilderConfig) -> syn::Result<TokenStream2> {
+        let impl_struct_name = config.name;
+        let builder_struct_name = utils::get_builder_struct_ident(config.name);
+        let (_, unset) = utils::get_state_marker_idents(config.name);
+        let vis = config.vis;
+        let states = States::new(config);
+        let params = &states.params;
+        let args = &states.args;
+        let where_clause = states.where_clause;
+        let unsets = states.params().map(|_| &unset);
+        let fields = config.fields.iter().map(|f| {
+            let field_name = f.ident.as_ref();
+            quote! {
+                #field_name: core::option::Option::None,
+            }
+        });
+        Ok(quote! {
+            impl<#(#params,)*> #impl_struct_name<#(#args,)*> #where_clause {
+                #vis fn builder() -> #builder_struct_name<#(#args,)* #(#unsets,)*> {
+                    #builder_struct_name {
+                        #(#fields)*
+                        __state: core::marker::PhantomData,
+                    }
+                }
+            }
+        })
+    }
+
+    /// 生成setter方法，setter总是按值接收构建器
+    ///
+    /// 必填字段的setter只在该字段未设置时存在，并返回该字段已设置的构建器，
+    /// `each`方法在任何状态下都可以调用，同样返回该字段已设置的构建器；
+    /// 非必填字段的setter不改变构建器的状态
+    /// ```ignore
+    /// impl CommandBuilder<CommandBuilderUnset> {
+    ///     pub fn executable(self, executable: String) -> CommandBuilder<CommandBuilderSet> { ... }
+    /// }
+    /// impl<__Executable> CommandBuilder<__Executable> {
+    ///     pub fn current_dir(mut self, current_dir: String) -> Self { ... }
+    /// }
+    /// ```
+    pub fn generate_typestate_setter(config: &BuilderConfig) -> syn::Result<TokenStream2> {
+        let builder_struct_name = utils::get_builder_struct_ident(config.name);
+        let (set, unset) = utils::get_state_marker_idents(config.name);
+        let vis = config.vis;
+        let states = States::new(config);
+        let params = &states.params;
+        let args = &states.args;
+        let where_clause = states.where_clause;
+        let all_states = states.params().collect::<Vec<_>>();
+        let setters = config.fields.iter().enumerate().map(|(index, f)| {
+            let field_name = f.ident.as_ref();
+            let ty = if utils::is_option(&f.ty) {
+                utils::inner_type(&f.ty)
+            } else {
+                Some(&f.ty)
+            };
+            let each = Self::field_builder_attr(f)
+                .and_then(|attr| utils::unwrap_single_attribute(attr, config::BUILDER_EACH_KEY))
+                .filter(|_| utils::is_vec(&f.ty));
+            // each方法与字段同名时不再生成整体设置的方法
+            let setter_needed = !each.as_ref().is_some_and(|lit_str| {
+                field_name.is_some_and(|name| name == lit_str.value().as_str())
+            });
+            let each_push = |fn_argument: &syn::Ident| {
+                quote! {
+                    match self.#field_name {
+                        core::option::Option::None => {
+                            self.#field_name = core::option::Option::Some(vec![#fn_argument])
+                        }
+                        core::option::Option::Some(ref mut v) => v.push(#fn_argument),
+                    }
+                }
+            };
+            let mut ret = TokenStream2::new();
+            if states.states[index].is_none() {
+                if setter_needed {
+                    ret.extend(quote! {
+                        impl<#(#params,)* #(#all_states,)*> #builder_struct_name<#(#args,)* #(#all_states,)*> #where_clause {
+                            #vis fn #field_name(mut self, #field_name: #ty) -> Self {
+                                self.#field_name = core::option::Option::Some(#field_name);
+                                self
+                            }
+                        }
+                    });
+                }
+                if let Some(ref lit_str) = each {
+                    let fn_name = syn::Ident::new(&lit_str.value(), lit_str.span());
+                    let inner = utils::inner_type(&f.ty);
+                    let push = each_push(&fn_name);
+                    ret.extend(quote! {
+                        impl<#(#params,)* #(#all_states,)*> #builder_struct_name<#(#args,)* #(#all_states,)*> #where_clause {
+                            #vis fn #fn_name(mut self, #fn_name: #inner) -> Self {
+                                #push
+                                self
+                            }
+                        }
+                    });
+                }
+                return ret;
+            }
+
+            let other_states = states.params_except(index).collect::<Vec<_>>();
+            let unset_args = states.args_with(index, &unset);
+            let set_args = states.args_with(index, &set);
+            // 状态改变后构建器类型不同，需要逐个移动字段
+            let others = config
+                .fields
+                .iter()
+                .filter(|other| other.ident != f.ident)
+                .map(|other| {
+                    let ident = other.ident.as_ref();
+                    quote!(#ident: self.#ident,)
+                })
+                .collect::<Vec<_>>();
+            if setter_needed {
+                ret.extend(quote! {
+                    impl<#(#params,)* #(#other_states,)*> #builder_struct_name<#(#args,)* #(#unset_args,)*> #where_clause {
+                        #vis fn #field_name(self, #field_name: #ty) -> #builder_struct_name<#(#args,)* #(#set_args,)*> {
+                            #builder_struct_name {
+                                #field_name: core::option::Option::Some(#field_name),
+                                #(#others)*
+                                __state: core::marker::PhantomData,
+                            }
+                        }
+                    }
+                });
+            }
+            if let Some(ref lit_str) = each {
+                let fn_name = syn::Ident::new(&lit_str.value(), lit_str.span());
+                let inner = utils::inner_type(&f.ty);
+                let push = each_push(&fn_name);
+                ret.extend(quote! {
+                    impl<#(#params,)* #(#all_states,)*> #builder_struct_name<#(#args,)* #(#all_states,)*> #where_clause {
+                        #vis fn #fn_name(mut self, #fn_name: #inner) -> #builder_struct_name<#(#args,)* #(#set_args,)*> {
+                            #push
+                            #builder_struct_name {
+                                #field_name: self.#field_name,
+                                #(#others)*
+                                __state: core::marker::PhantomData,
+                            }
+                        }
+                    }
+                });
+            }
+            ret
+        });
+        Ok(quote! {
+            #(#setters)*
+        })
+    }
+
+    /// 生成`build`方法，它只在所有必填字段都已设置时存在，因此直接返回原结构体
+    /// ```ignore
+    /// impl CommandBuilder<CommandBuilderSet> {
+    ///     pub fn build(self) -> Command { ... }
+    /// }
+    /// ```
+    pub fn generate_typestate_build(config: &BuilderConfig) -> syn::Result<TokenStream2> {
+        let src_struct_name = config.name;
+        let builder_struct_name = utils::get_builder_struct_ident(config.name);
+        let (set, _) = utils::get_state_marker_idents(config.name);
+        let vis = config.vis;
+        let states = States::new(config);
+        let params = &states.params;
+        let args = &states.args;
+        let where_clause = states.where_clause;
+        let sets = states.params().map(|_| &set);
+        let fields = Self::build_fields(config, Pattern::Owned, |ident| {
+            let message = format!(
+                "field {} is guaranteed to be set by the builder state",
+                ident
+            );
+            quote!(.unwrap_or_else(|| core::unreachable!(#message)))
+        })?;
+        let struct_default = Self::struct_default(config);
+        let build_bound = Self::build_bound(config, Pattern::Owned);
+        Ok(quote! {
+            impl<#(#params,)*> #builder_struct_name<#(#args,)* #(#sets,)*> #where_clause {
+                #vis fn build(self) -> #src_struct_name<#(#args,)*> #build_bound {
+                    #struct_default
+                    #src_struct_name {
+                        #(#fields)*
+                    }
+                }
+            }
+        })
+    }
+}
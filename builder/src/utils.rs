@@ -1,7 +1,10 @@
 //! 存放一些快捷判断逻辑
 #![allow(dead_code)]
 
-use quote::format_ident;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
 use crate::config;
 
 /// 判断是否是Option
@@ -71,6 +74,13 @@ pub fn is_vec(ty: &syn::Type) -> bool {
     is_type(ty, "Vec")
 }
 
+/// 解析`#[builder(...)]`中以逗号分隔的各项，例如`each = "arg", default`
+pub fn builder_attribute_items(
+    attr: &syn::Attribute,
+) -> syn::Result<Punctuated<syn::Meta, syn::Token![,]>> {
+    attr.parse_args_with(Punctuated::parse_terminated)
+}
+
 /// 判断一个标签中是否存在特定标签，如果存在，那么就取出为 `syn::LitStr`
 ///
 /// # Arguments
@@ -84,34 +94,123 @@ pub fn is_vec(ty: &syn::Type) -> bool {
 /// ```ignore
 /// use syn::parse_quote;
 /// let attrbute:syn::Attribute = parse_quote!(
-///     #[builder(each = "arg")]
+///     #[builder(each = "arg", default)]
 /// );
 /// if let Some(lit_str)=unwrap_single_attribute(&attrbute,"each"){
 /// ...
 /// }
 /// ```
 pub fn unwrap_single_attribute(attr: &syn::Attribute, target_attr: &str) -> Option<syn::LitStr> {
-    if let Ok(meta) = attr.parse_args::<syn::MetaNameValue>() {
-        if meta.path.is_ident(target_attr) {
-            if let syn::Expr::Lit(syn::ExprLit {
-                lit: syn::Lit::Str(lit_str),
+    builder_attribute_items(attr)
+        .ok()?
+        .into_iter()
+        .find_map(|meta| match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }),
                 ..
-            }) = meta.value
-            {
-                return Some(lit_str);
+            }) if path.is_ident(target_attr) => Some(lit_str),
+            _ => None,
+        })
+}
+
+/// 判断一个标签中是否存在不带值的特定标签，例如`#[builder(default)]`中的`default`
+pub fn has_path_attribute(attr: &syn::Attribute, target_attr: &str) -> bool {
+    builder_attribute_items(attr).is_ok_and(|items| {
+        items
+            .iter()
+            .any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(target_attr)))
+    })
+}
+
+/// 获取标签中第一个不在`known`之中的左值，用于打印日志
+pub fn unknown_attribute_key(attr: &syn::Attribute, known: &[&str]) -> Option<syn::Ident> {
+    builder_attribute_items(attr)
+        .ok()?
+        .iter()
+        .filter_map(|meta| meta.path().get_ident())
+        .find(|ident| !known.iter().any(|key| *ident == key))
+        .cloned()
+}
+
+/// 检查标签中每个已知key的写法是否符合`shapes`，写法不符时返回指向该项的错误
+///
+/// 例如字段上的`default = 8`或结构体上的`default = "expr"`，
+/// 不检查时这些项既不是未知key，又匹配不到任何写法，会被静默忽略
+pub fn check_attribute_shapes(
+    attr: &syn::Attribute,
+    shapes: &[(&str, config::KeyShape)],
+) -> syn::Result<()> {
+    for meta in builder_attribute_items(attr)? {
+        let Some((key, shape)) = shapes.iter().find(|(key, _)| meta.path().is_ident(key)) else {
+            continue;
+        };
+        let is_str = matches!(
+            meta,
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(_),
+                    ..
+                }),
+                ..
+            })
+        );
+        let is_path = matches!(meta, syn::Meta::Path(_));
+        let message = match shape {
+            config::KeyShape::Path if !is_path => format!("{}不接受取值，应写作{}", key, key),
+            config::KeyShape::Str([]) if !is_str => {
+                format!("{}需要一个字符串值，应写作{} = \"...\"", key, key)
             }
-        }
+            config::KeyShape::Str(values) if !is_str => format!(
+                "{}需要字符串\"{}\"之一，例如{} = \"{}\"",
+                key,
+                values.join("\"、\""),
+                key,
+                values[0]
+            ),
+            config::KeyShape::PathOrStr if !is_path && !is_str => {
+                format!("{}应写作{}或{} = \"...\"", key, key, key)
+            }
+            _ => continue,
+        };
+        return Err(syn::Error::new_spanned(meta, message));
     }
-
-    None
+    Ok(())
 }
 
-/// 获取一个`syn::MetaNameValue`attr的左值，用于打印日志
-pub fn meta_name_value_attr_left_value(attr: &syn::Attribute) -> Option<syn::Ident> {
-    if let Ok(ref meta) = attr.parse_args::<syn::MetaNameValue>() {
-        return meta.path.get_ident().cloned();
+/// 获取字段上`#[builder(default)]`或`#[builder(default = "expr")]`给出的缺省值
+///
+/// returns: 没有缺省值时为`None`，`#[builder(default)]`为`Default::default()`
+pub fn field_default(f: &syn::Field) -> syn::Result<Option<TokenStream2>> {
+    for attr in f
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident(config::PROC_ATTR_BUILDER))
+    {
+        if let Some(lit_str) = unwrap_single_attribute(attr, config::BUILDER_DEFAULT_KEY) {
+            let expr = lit_str.parse::<syn::Expr>()?;
+            return Ok(Some(expr.into_token_stream()));
+        }
+        if has_path_attribute(attr, config::BUILDER_DEFAULT_KEY) {
+            return Ok(Some(quote!(core::default::Default::default())));
+        }
     }
-    None
+    Ok(None)
+}
+
+/// 判断字段是否必须在`build`之前设置，即没有任何缺省值的非`Option`字段
+///
+/// # Arguments
+///
+/// * `f`: 字段
+/// * `struct_default`: 结构体上是否有`#[builder(default)]`
+#[inline]
+pub fn is_required(f: &syn::Field, struct_default: bool) -> bool {
+    !struct_default && !is_option(&f.ty) && !matches!(field_default(f), Ok(Some(_)))
 }
 
 /// 格式化一个将原标识符格式化为Builder构建器所需标识符
@@ -126,3 +225,98 @@ pub fn meta_name_value_attr_left_value(attr: &syn::Attribute) -> Option<syn::Ide
 pub fn get_builder_struct_ident(src_ident:&syn::Ident)->syn::Ident{
     format_ident!("{}{}",src_ident,config::BUILDER_SUFFIX)
 }
+
+/// 格式化Builder构建失败时的错误枚举标识符
+/// # Arguments
+/// * `src_ident`: 目标结构体的原始名称
+///
+/// 原标识符
+/// `Command`
+/// 现标识符
+/// `CommandBuilderError`
+#[inline]
+pub fn get_builder_error_ident(src_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("{}{}", src_ident, config::BUILDER_ERROR_SUFFIX)
+}
+
+/// 格式化错误枚举引用`alloc`时所用的`extern crate`别名
+/// `alloc`不在外部预导入中，`no_std`与标准库的crate都需要先声明它才能通过绝对路径使用
+/// # Arguments
+/// * `src_ident`: 目标结构体的原始名称
+///
+/// 原标识符
+/// `Command`
+/// 现标识符
+/// `__CommandBuilderAlloc`
+#[inline]
+pub fn get_builder_alloc_ident(src_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("__{}{}", src_ident, config::BUILDER_ALLOC_SUFFIX)
+}
+
+/// 格式化缺少某个必填字段时的错误变体标识符
+/// # Arguments
+/// * `field_ident`: 字段名称
+///
+/// 原标识符
+/// `current_dir`
+/// 现标识符
+/// `MissingCurrentDir`
+pub fn get_missing_variant_ident(field_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("Missing{}", to_upper_camel_case(field_ident), span = field_ident.span())
+}
+
+/// 格式化typestate构建器中记录某个必填字段是否已设置的泛型参数标识符
+/// # Arguments
+/// * `field_ident`: 字段名称
+///
+/// 原标识符
+/// `current_dir`
+/// 现标识符
+/// `__CurrentDir`
+pub fn get_state_param_ident(field_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("__{}", to_upper_camel_case(field_ident), span = field_ident.span())
+}
+
+/// 格式化typestate构建器中表示字段已设置与未设置的标记类型标识符
+///
+/// 原标识符
+/// `Command`
+/// 现标识符
+/// `(CommandBuilderSet, CommandBuilderUnset)`
+#[inline]
+pub fn get_state_marker_idents(src_ident: &syn::Ident) -> (syn::Ident, syn::Ident) {
+    (
+        format_ident!("{}{}Set", src_ident, config::BUILDER_SUFFIX),
+        format_ident!("{}{}Unset", src_ident, config::BUILDER_SUFFIX),
+    )
+}
+
+/// 将蛇形命名的标识符转换为驼峰形式，例如`current_dir`转换为`CurrentDir`
+fn to_upper_camel_case(ident: &syn::Ident) -> String {
+    ident
+        .unraw()
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// 取出泛型参数在使用处的写法，例如`<'a, T: Clone, const N: usize>`得到`'a`、`T`与`N`
+pub fn generic_args(generics: &syn::Generics) -> Vec<TokenStream2> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            syn::GenericParam::Type(param) => param.ident.to_token_stream(),
+            syn::GenericParam::Const(param) => param.ident.to_token_stream(),
+        })
+        .collect()
+}
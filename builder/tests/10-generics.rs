@@ -0,0 +1,57 @@
+// Structs that are generic over a type or borrow data with a lifetime get a
+// builder with the same generic parameters, bounds and where clause. Every
+// generated item, including the builder struct itself, carries them. The
+// default build(&self) clones every field, so it requires the field types to
+// be Clone on the method itself: a builder over an unbounded T always
+// compiles, and only calling build with a T that is not Clone is an error.
+//
+// 带有泛型参数或生命周期的结构体，其构建器拥有相同的泛型参数、约束与where子句，
+// 所有生成的内容（包括构建器结构体本身）都需要带上它们。默认的build(&self)会克隆每个字段，
+// 字段类型实现Clone的约束写在build方法上：泛型参数没有任何约束时构建器依然能够生成，
+// 只有对不实现Clone的类型调用build才会报错。
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+#[derive(Builder)]
+pub struct Connection<'a, T: Clone, const N: usize>
+where
+    T: Debug,
+{
+    host: &'a str,
+    backend: T,
+    #[builder(each = "retry")]
+    retries: Vec<[u8; N]>,
+    label: Option<&'a str>,
+}
+
+#[derive(Builder)]
+pub struct Wrapper<T> {
+    value: T,
+    extra: Option<T>,
+}
+
+pub struct NotClone;
+
+fn main() {
+    let host = String::from("localhost");
+    let connection = Connection::<_, 2>::builder()
+        .host(&host)
+        .backend(vec![1u32, 2, 3])
+        .retry([1, 2])
+        .retry([3, 4])
+        .build()
+        .unwrap();
+
+    assert_eq!(connection.host, "localhost");
+    assert_eq!(connection.backend, vec![1, 2, 3]);
+    assert_eq!(connection.retries, vec![[1, 2], [3, 4]]);
+    assert_eq!(connection.label, None);
+
+    let wrapper = Wrapper::builder().value(String::from("a")).build().unwrap();
+    assert_eq!(wrapper.value, "a");
+    assert_eq!(wrapper.extra, None);
+
+    let mut builder = Wrapper::builder();
+    builder.value(NotClone).extra(NotClone);
+}
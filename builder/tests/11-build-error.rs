@@ -0,0 +1,67 @@
+// The build method reports which required field is missing through a
+// generated error enum named after the struct. It has one variant per
+// required field, named Missing followed by the field name in camel case, and
+// a Validation variant carrying the String returned by the function named in
+// #[builder(validate = "...")], which build calls on the finished struct. The
+// enum only needs core and alloc. It implements Display and Error, so it
+// still works with the ? operator in functions returning Box<dyn Error>.
+//
+// build方法通过以结构体命名的错误枚举报告缺少了哪个必填字段。每个必填字段对应一个
+// 以Missing加上驼峰形式字段名命名的变体，另有一个Validation变体，携带#[builder(validate = "...")]
+// 所指定的函数返回的String，build在返回前以构建好的结构体调用该函数。该枚举只依赖core与alloc，
+// 并实现了Display与Error，因此在返回Box<dyn Error>的函数中依然可以使用?运算符。
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(validate = "Command::check")]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+    env_vars: Vec<String>,
+}
+
+impl Command {
+    fn check(&self) -> Result<(), String> {
+        if self.executable.is_empty() {
+            return Err(format!("{} env vars given without an executable", self.env_vars.len()));
+        }
+        Ok(())
+    }
+}
+
+fn build_ls() -> Result<Command, Box<dyn std::error::Error>> {
+    Ok(Command::builder()
+        .executable("ls".to_owned())
+        .env_vars(vec![])
+        .build()?)
+}
+
+fn main() {
+    let error = Command::builder().env_vars(vec![]).build().err().unwrap();
+    assert_eq!(error, CommandBuilderError::MissingExecutable);
+    assert_eq!(error.to_string(), "struct Command not set field executable");
+
+    let error = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(error, CommandBuilderError::MissingEnvVars));
+
+    let error = Command::builder()
+        .executable(String::new())
+        .env_vars(vec!["A=1".to_owned(), "B=2".to_owned()])
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        CommandBuilderError::Validation("2 env vars given without an executable".to_owned()),
+    );
+    assert_eq!(error.to_string(), "2 env vars given without an executable");
+
+    let command = build_ls().unwrap();
+    assert_eq!(command.executable, "ls");
+    assert!(command.current_dir.is_none());
+}
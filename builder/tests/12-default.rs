@@ -0,0 +1,76 @@
+// Fields marked #[builder(default)] no longer have to be set before calling
+// build; they fall back to Default::default(). #[builder(default = "...")]
+// gives an arbitrary expression to fall back to instead. A #[builder(default)]
+// on the struct itself fills every unset field from the struct's own Default
+// implementation. Fields with a fallback value get no Missing variant in the
+// error enum.
+//
+// 带有#[builder(default)]的字段在调用build之前不必设置，未设置时取Default::default()；
+// #[builder(default = "...")]则给出任意的缺省表达式。结构体上的#[builder(default)]
+// 会用结构体自身的Default实现填充所有未设置的字段。有缺省值的字段在错误枚举中没有对应的Missing变体。
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", default)]
+    args: Vec<String>,
+    #[builder(default = "String::from(\"/tmp\")")]
+    current_dir: String,
+    #[builder(default = "Some(3)")]
+    retries: Option<u8>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(default)]
+pub struct Limits {
+    open_files: u32,
+    threads: Option<u16>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            open_files: 1024,
+            threads: Some(8),
+        }
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.current_dir, "/tmp");
+    assert_eq!(command.retries, Some(3));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .current_dir("/src".to_owned())
+        .retries(0)
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir, "/src");
+    assert_eq!(command.retries, Some(0));
+
+    let error = Command::builder().build().err().unwrap();
+    match error {
+        CommandBuilderError::MissingExecutable => {}
+        CommandBuilderError::Validation(_) => unreachable!(),
+    }
+
+    assert_eq!(Limits::builder().build().unwrap(), Limits::default());
+    let limits = Limits::builder().open_files(16).build().unwrap();
+    assert_eq!(
+        limits,
+        Limits {
+            open_files: 16,
+            threads: Some(8),
+        }
+    );
+}
@@ -0,0 +1,73 @@
+// By default the setters borrow the builder mutably and build clones every
+// field, so all field types have to implement Clone. #[builder(pattern =
+// "owned")] makes the setters take and return the builder by value and lets
+// build consume it. #[builder(pattern = "mutable")] keeps the &mut setters
+// while build takes &mut self and moves the values out with take(), leaving
+// the builder empty. Neither of them needs Clone. #[builder(pattern =
+// "clone")] names the default style explicitly.
+//
+// 默认的setter以可变借用使用构建器，build会克隆每个字段，因此所有字段类型都必须实现Clone。
+// #[builder(pattern = "owned")]使setter按值接收并返回构建器，build消耗构建器；
+// #[builder(pattern = "mutable")]保留&mut形式的setter，build接收&mut self并用take()取走各个值，
+// 之后构建器为空。两者都不需要Clone。#[builder(pattern = "clone")]显式选择默认风格。
+
+use derive_builder::Builder;
+
+pub struct Handle(u32);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Task {
+    handle: Handle,
+    callback: Box<dyn Fn(u32) -> u32>,
+    #[builder(each = "tag")]
+    tags: Vec<Handle>,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Job {
+    handle: Handle,
+    #[builder(default)]
+    retries: u8,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "clone")]
+pub struct Label {
+    text: String,
+}
+
+fn main() {
+    let task = Task::builder()
+        .handle(Handle(7))
+        .callback(Box::new(|x| x * 2))
+        .tag(Handle(1))
+        .tag(Handle(2))
+        .build()
+        .unwrap();
+    assert_eq!(task.handle.0, 7);
+    assert_eq!((task.callback)(21), 42);
+    assert_eq!(task.tags.len(), 2);
+    assert!(task.name.is_none());
+
+    let mut builder = Job::builder();
+    builder.name("nightly".to_owned());
+    assert!(matches!(builder.build(), Err(JobBuilderError::MissingHandle)));
+
+    builder.handle(Handle(3));
+    let job = builder.build().unwrap();
+    assert_eq!(job.handle.0, 3);
+    assert_eq!(job.retries, 0);
+    assert_eq!(job.name.as_deref(), Some("nightly"));
+
+    // build取走了所有字段
+    assert!(matches!(builder.build(), Err(JobBuilderError::MissingHandle)));
+
+    let mut builder = Label::builder();
+    builder.text("first".to_owned());
+    assert_eq!(builder.build().unwrap().text, "first");
+    assert_eq!(builder.build().unwrap().text, "first");
+}
@@ -0,0 +1,52 @@
+// With #[builder(typestate)] a forgotten required field is caught at compile
+// time instead of when build runs. The builder carries one type parameter per
+// required field recording whether it has been set, the setter of a required
+// field is only available while that field is unset, and build only exists
+// once every required field is set. Because of that build returns the struct
+// directly rather than a Result. Optional fields, fields with a default and
+// repeated fields can be set at any time.
+//
+// 使用#[builder(typestate)]时，遗漏必填字段会在编译期而不是build运行时被发现。
+// 构建器为每个必填字段带有一个记录其是否已设置的类型参数，必填字段的setter只在该字段未设置时可用，
+// build只在所有必填字段都已设置后才存在，因此build直接返回结构体而不是Result。
+// 可选字段、有缺省值的字段以及重复字段可以在任何时候设置。
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command<'a, T: Clone> {
+    executable: &'a str,
+    backend: T,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(default = "8")]
+    jobs: u8,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, default)]
+#[derive(Default)]
+pub struct Limits {
+    open_files: u32,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .current_dir("/src".to_owned())
+        .backend(vec![1, 2])
+        .executable("cargo")
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.backend, vec![1, 2]);
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.jobs, 8);
+    assert_eq!(command.current_dir.as_deref(), Some("/src"));
+
+    assert_eq!(Limits::builder().build().open_files, 0);
+    assert_eq!(Limits::builder().open_files(64).build().open_files, 64);
+}
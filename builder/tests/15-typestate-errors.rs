@@ -0,0 +1,31 @@
+// A typestate builder has no build method until every required field is set,
+// and the setter of a required field disappears once it has been called.
+// Unknown keys in a field's builder attribute are reported the same way as for
+// the runtime builder.
+//
+// typestate构建器在所有必填字段设置之前没有build方法，必填字段的setter调用之后也不再存在。
+// 字段builder标签中的未知属性与运行时构建器一样报错。
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(each = "arg", defualt)]
+    args: Vec<String>,
+}
+
+fn main() {
+    let _ = Command::builder().executable("cargo".to_owned()).build();
+
+    let _ = Command::builder()
+        .executable("cargo".to_owned())
+        .executable("rustc".to_owned());
+}
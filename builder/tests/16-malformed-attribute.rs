@@ -0,0 +1,58 @@
+// Keys the builder attribute knows about must still be written in a form it
+// supports. A field's default takes no value or a string expression, each
+// takes a string, and on the struct default and typestate take no value while
+// validate takes a string and pattern one of the strings "owned", "mutable" or
+// "clone". Any other form is reported at the offending item
+// instead of being silently ignored.
+//
+// builder标签认识的key也必须以受支持的写法出现。字段上的default不带值或带字符串表达式，
+// each带字符串；结构体上的default与typestate不带值，validate带字符串，
+// pattern带字符串"owned"、"mutable"或"clone"之一。
+// 其他写法会在对应的项上报错，而不是被静默忽略。
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default = 8)]
+    retries: u8,
+}
+
+#[derive(Builder)]
+pub struct Job {
+    #[builder(each(arg))]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(default = "Limits { open_files: 1 }")]
+pub struct Limits {
+    open_files: u32,
+}
+
+#[derive(Builder)]
+#[builder(typestate = "yes")]
+pub struct Request {
+    url: String,
+}
+
+#[derive(Builder)]
+#[builder(validate = Request::check)]
+pub struct Response {
+    status: u16,
+}
+
+#[derive(Builder)]
+#[builder(pattern = owned)]
+pub struct Task {
+    id: u32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "borrowed")]
+pub struct Query {
+    sql: String,
+}
+
+fn main() {}